@@ -1,8 +1,219 @@
-use bitcoin::{secp256k1, PublicKey};
-use elements::{Address, Script, PubkeyHash, ScriptHash, WPubkeyHash, WScriptHash};
+use bitcoin::{self, secp256k1, PublicKey};
+use bitcoin::blockdata::constants::MAX_SCRIPT_ELEMENT_SIZE;
+use bitcoin::util::base58;
+use elements::{bech32, blech32};
+use elements::opcodes::all::OP_CHECKMULTISIG;
+use elements::script::Builder;
+use bitcoin::hashes::Hash;
+use elements::address::Payload;
+use elements::{Address, AddressError, Script, PubkeyHash, ScriptHash, WPubkeyHash, WScriptHash};
+use elements::schnorr::UntweakedPublicKey;
+use elements::slip77::MasterBlindingKey;
+use elements::taproot::TapBranchHash;
 use serde::{Deserialize, Serialize};
 
-use ::Network;
+use ::{Chain, GetInfo, HexBytes, Network};
+
+/// Parse an address for any of the known networks, including the given one if it's custom.
+pub fn parse_address(s: &str, network: Option<Network>) -> Result<Address, AddressError> {
+	if let Some(Network::Custom(params)) = network {
+		let custom_hrp = match s.rfind('1') {
+			Some(sep) => {
+				let hrp = s[..sep].to_lowercase();
+				hrp == params.bech_hrp || hrp == params.blech_hrp
+			}
+			None => false,
+		};
+		match Address::parse_with_params(s, params) {
+			Ok(address) => return Ok(address),
+			// The known networks would only fail on the custom HRP.
+			Err(e) if custom_hrp => return Err(e),
+			Err(_) => {}
+		}
+	}
+	s.parse()
+}
+
+/// The kind of failure encountered when parsing an address.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressParseFailure {
+	/// The checksum doesn't match the data.
+	Checksum,
+	/// The encoded data or witness program has an invalid length.
+	Length,
+	/// The base58 prefix or the HRP doesn't belong to a known network.
+	UnknownPrefix,
+	/// The address contains characters that are not valid in the encoding.
+	InvalidCharacter,
+	/// The witness version is invalid.
+	WitnessVersion,
+	/// The bech32 or bech32m variant doesn't match the witness version.
+	EncodingVariant,
+	/// The blinding pubkey is not a valid public key.
+	BlindingPubkey,
+	/// The address is valid, but doesn't belong to the expected network.
+	WrongNetwork,
+	/// The address is a Bitcoin address.
+	BitcoinAddress,
+	Other,
+}
+
+impl<'a> From<&'a AddressError> for AddressParseFailure {
+	fn from(e: &'a AddressError) -> AddressParseFailure {
+		match *e {
+			AddressError::Base58(ref e) => match *e {
+				base58::Error::BadChecksum(..) => AddressParseFailure::Checksum,
+				base58::Error::BadByte(_) => AddressParseFailure::InvalidCharacter,
+				base58::Error::InvalidLength(_) | base58::Error::TooShort(_) => AddressParseFailure::Length,
+				base58::Error::InvalidAddressVersion(_) => AddressParseFailure::UnknownPrefix,
+				_ => AddressParseFailure::Other,
+			},
+			AddressError::Bech32(ref e) | AddressError::Blech32(ref e) => match *e {
+				bech32::Error::InvalidChecksum => AddressParseFailure::Checksum,
+				bech32::Error::InvalidLength | bech32::Error::InvalidPadding => AddressParseFailure::Length,
+				bech32::Error::InvalidChar(_) | bech32::Error::MixedCase | bech32::Error::MissingSeparator => {
+					AddressParseFailure::InvalidCharacter
+				}
+				_ => AddressParseFailure::Other,
+			},
+			AddressError::InvalidWitnessVersion(_) => AddressParseFailure::WitnessVersion,
+			AddressError::InvalidWitnessProgramLength(_) | AddressError::InvalidSegwitV0ProgramLength(_) => {
+				AddressParseFailure::Length
+			}
+			AddressError::InvalidWitnessEncoding | AddressError::InvalidSegwitV0Encoding => {
+				AddressParseFailure::EncodingVariant
+			}
+			AddressError::InvalidBlindingPubKey(_) => AddressParseFailure::BlindingPubkey,
+			AddressError::InvalidAddress(_) => AddressParseFailure::Other,
+		}
+	}
+}
+
+/// Diagnostics on why an address could not be used.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct AddressParseDiagnostics {
+	/// The encoding the address appears to use: base58, bech32 or blech32.
+	pub encoding: Option<String>,
+	pub failure: AddressParseFailure,
+	pub error: String,
+	/// The networks whose base58 prefix or HRP match the address.
+	pub matching_networks: Vec<Network>,
+	/// The network the address most likely belongs to.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub likely_network: Option<Network>,
+	/// The Bitcoin network, if this is a Bitcoin address.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitcoin_network: Option<bitcoin::Network>,
+}
+
+/// All known networks, including the given one if it's custom.
+fn known_networks(network: Option<Network>) -> Vec<Network> {
+	let mut networks = vec![Network::Liquid, Network::LiquidTestnet, Network::ElementsRegtest];
+	if let Some(custom @ Network::Custom(_)) = network {
+		networks.push(custom);
+	}
+	networks
+}
+
+/// Parse an address and diagnose the failure if it can't be parsed.
+///
+/// If an expected network is given, addresses of other networks are rejected as well.
+pub fn parse_address_diagnosed(
+	s: &str,
+	expected: Option<Network>,
+) -> Result<Address, AddressParseDiagnostics> {
+	let err = match parse_address(s, expected) {
+		Ok(address) => {
+			let network = Network::from_params(address.params);
+			return match expected {
+				Some(expected) if network != expected => Err(AddressParseDiagnostics {
+					encoding: Some(match address.payload {
+						Payload::WitnessProgram { .. } if address.blinding_pubkey.is_some() => "blech32",
+						Payload::WitnessProgram { .. } => "bech32",
+						_ => "base58",
+					}.to_owned()),
+					failure: AddressParseFailure::WrongNetwork,
+					error: "address doesn't belong to the expected network".to_owned(),
+					matching_networks: vec![network],
+					likely_network: Some(network),
+					bitcoin_network: None,
+				}),
+				_ => Ok(address),
+			};
+		}
+		Err(e) => e,
+	};
+
+	let mut diag = AddressParseDiagnostics {
+		encoding: None,
+		failure: AddressParseFailure::from(&err),
+		error: err.to_string(),
+		matching_networks: Vec::new(),
+		likely_network: None,
+		bitcoin_network: None,
+	};
+
+	// Bech32 and blech32 addresses are recognized by their HRP,
+	// which is the part before the last separator.
+	let networks = known_networks(expected);
+	if let Some(sep) = s.rfind('1') {
+		let hrp = s[..sep].to_lowercase();
+		for &network in &networks {
+			let params = network.address_params();
+			if hrp == params.bech_hrp {
+				diag.encoding = Some("bech32".to_owned());
+				diag.matching_networks.push(network);
+			} else if hrp == params.blech_hrp {
+				diag.encoding = Some("blech32".to_owned());
+				diag.matching_networks.push(network);
+			}
+		}
+	}
+
+	if diag.encoding.is_none() {
+		// Elements only tries base58 for unknown HRPs, so its error is misleading.
+		if bech32::decode(s).is_ok() {
+			diag.encoding = Some("bech32".to_owned());
+			diag.failure = AddressParseFailure::UnknownPrefix;
+			diag.error = "unknown bech32 HRP".to_owned();
+		} else if blech32::decode(s).is_ok() {
+			diag.encoding = Some("blech32".to_owned());
+			diag.failure = AddressParseFailure::UnknownPrefix;
+			diag.error = "unknown blech32 HRP".to_owned();
+		} else if let Ok(data) = base58::from(s) {
+			// Don't check the checksum here so that we can still match the prefix.
+			diag.encoding = Some("base58".to_owned());
+			if let Some(&prefix) = data.first() {
+				for &network in &networks {
+					let params = network.address_params();
+					if prefix == params.p2pkh_prefix || prefix == params.p2sh_prefix
+						|| prefix == params.blinded_prefix
+					{
+						diag.matching_networks.push(network);
+					}
+				}
+			}
+		}
+		if diag.encoding.is_some() && diag.matching_networks.is_empty()
+			&& diag.failure == AddressParseFailure::Other
+		{
+			diag.failure = AddressParseFailure::UnknownPrefix;
+		}
+	}
+
+	if let Ok(address) = s.parse::<bitcoin::Address>() {
+		diag.failure = AddressParseFailure::BitcoinAddress;
+		diag.error = "this is a Bitcoin address, not an Elements address".to_owned();
+		diag.bitcoin_network = Some(address.network);
+	}
+
+	diag.likely_network = match expected {
+		Some(expected) if diag.matching_networks.contains(&expected) => Some(expected),
+		_ => diag.matching_networks.first().cloned(),
+	};
+	Err(diag)
+}
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct AddressInfo {
@@ -21,11 +232,100 @@ pub struct AddressInfo {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub witness_script_hash: Option<WScriptHash>,
 	#[serde(skip_serializing_if = "Option::is_none")]
+	pub taproot_output_key: Option<secp256k1::XOnlyPublicKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub encoding: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub blinding_pubkey: Option<secp256k1::PublicKey>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub unconfidential: Option<Address>,
 }
 
+impl GetInfo<AddressInfo> for Address {
+	/// The network is taken from the address params.
+	fn get_info(&self, _chain: Chain) -> AddressInfo {
+		let script_pk = self.script_pubkey();
+
+		let mut info = AddressInfo {
+			network: Network::from_params(self.params),
+			script_pub_key: hal::tx::OutputScriptInfo {
+				hex: Some(script_pk.to_bytes().into()),
+				asm: Some(script_pk.asm()),
+				address: None,
+				type_: None,
+			},
+			type_: None,
+			pubkey_hash: None,
+			script_hash: None,
+			witness_pubkey_hash: None,
+			witness_script_hash: None,
+			taproot_output_key: None,
+			witness_program_version: None,
+			encoding: None,
+			blinding_pubkey: self.blinding_pubkey,
+			unconfidential: if self.blinding_pubkey.is_some() {
+				Some(Address {
+					params: self.params,
+					payload: self.payload.clone(),
+					blinding_pubkey: None,
+				})
+			} else {
+				None
+			},
+		};
+
+		match self.payload {
+			Payload::PubkeyHash(pkh) => {
+				info.type_ = Some("p2pkh".to_owned());
+				info.encoding = Some("base58".to_owned());
+				info.pubkey_hash = Some(pkh);
+			}
+			Payload::ScriptHash(sh) => {
+				info.type_ = Some("p2sh".to_owned());
+				info.encoding = Some("base58".to_owned());
+				info.script_hash = Some(sh);
+			}
+			Payload::WitnessProgram {
+				version,
+				ref program,
+			} => {
+				let version = version.to_u8() as usize;
+				info.witness_program_version = Some(version);
+
+				// Parsing enforces bech32 for v0 and bech32m for v1+ programs.
+				info.encoding = Some(match (self.blinding_pubkey.is_some(), version) {
+					(false, 0) => "bech32",
+					(false, _) => "bech32m",
+					(true, 0) => "blech32",
+					(true, _) => "blech32m",
+				}.to_owned());
+
+				if version == 0 {
+					if program.len() == 20 {
+						info.type_ = Some("p2wpkh".to_owned());
+						info.witness_pubkey_hash = 
+							Some(WPubkeyHash::from_slice(program).expect("size 20"));
+					} else if program.len() == 32 {
+						info.type_ = Some("p2wsh".to_owned());
+						info.witness_script_hash =
+							Some(WScriptHash::from_slice(program).expect("size 32"));
+					} else {
+						info.type_ = Some("invalid-witness-program".to_owned());
+					}
+				} else if version == 1 && program.len() == 32 {
+					info.type_ = Some("p2tr".to_owned());
+					// Any 32-byte program is a valid address, even if it's not a valid key.
+					info.taproot_output_key = secp256k1::XOnlyPublicKey::from_slice(program).ok();
+				} else {
+					info.type_ = Some("unknown-witness-program-version".to_owned());
+				}
+			}
+		}
+
+		info
+	}
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
 pub struct Addresses {
 	#[serde(skip_serializing_if = "Option::is_none")]
@@ -40,6 +340,43 @@ pub struct Addresses {
 	pub p2wsh: Option<Address>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub p2shwsh: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2tr: Option<Address>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub blinding_keys: Option<BlindingKeys>,
+}
+
+/// The blinding private keys of the confidential [Addresses].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub struct BlindingKeys {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2pkh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2wpkh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2shwpkh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2sh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2wsh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2shwsh: Option<secp256k1::SecretKey>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub p2tr: Option<secp256k1::SecretKey>,
+}
+
+/// Blind the address with the SLIP-77 blinding key for its scriptPubKey.
+/// Returns the blinding private key used.
+fn blind_slip77(
+	address: &mut Option<Address>,
+	master_key: &MasterBlindingKey,
+) -> Option<secp256k1::SecretKey> {
+	let secp = secp256k1::Secp256k1::signing_only();
+	address.as_mut().map(|addr| {
+		let blinding_key = master_key.derive_blinding_key(&addr.script_pubkey());
+		*addr = addr.to_confidential(secp256k1::PublicKey::from_secret_key(&secp, &blinding_key));
+		blinding_key
+	})
 }
 
 impl Addresses {
@@ -55,11 +392,224 @@ impl Addresses {
 
 	pub fn from_script(script: &Script, blinder: Option<secp256k1::PublicKey>, network: Network) -> Addresses {
 		let params = network.address_params();
+		// A p2sh redeem script is pushed on the stack, so it's limited to the push size.
+		let p2sh = if script.len() <= MAX_SCRIPT_ELEMENT_SIZE {
+			Some(Address::p2sh(&script, blinder, params))
+		} else {
+			None
+		};
 		Addresses {
-			p2sh: Some(Address::p2sh(&script, blinder, params)),
+			p2sh,
 			p2wsh: Some(Address::p2wsh(&script, blinder, params)),
 			p2shwsh: Some(Address::p2shwsh(&script, blinder, params)),
 			..Default::default()
 		}
 	}
+
+	/// Create a taproot address from an internal key and an optional script tree merkle root.
+	pub fn from_internal_key(
+		internal_key: UntweakedPublicKey,
+		merkle_root: Option<TapBranchHash>,
+		blinder: Option<secp256k1::PublicKey>,
+		network: Network,
+	) -> Addresses {
+		let secp = secp256k1::Secp256k1::verification_only();
+		let params = network.address_params();
+		Addresses {
+			p2tr: Some(Address::p2tr(&secp, internal_key, merkle_root, blinder, params)),
+			..Default::default()
+		}
+	}
+
+	/// Blind all addresses using the blinding keys derived from the SLIP-77 master key.
+	/// The blinding private keys are stored in [Addresses::blinding_keys].
+	pub fn blind_slip77(&mut self, master_key: &MasterBlindingKey) {
+		self.blinding_keys = Some(BlindingKeys {
+			p2pkh: blind_slip77(&mut self.p2pkh, master_key),
+			p2wpkh: blind_slip77(&mut self.p2wpkh, master_key),
+			p2shwpkh: blind_slip77(&mut self.p2shwpkh, master_key),
+			p2sh: blind_slip77(&mut self.p2sh, master_key),
+			p2wsh: blind_slip77(&mut self.p2wsh, master_key),
+			p2shwsh: blind_slip77(&mut self.p2shwsh, master_key),
+			p2tr: blind_slip77(&mut self.p2tr, master_key),
+		});
+	}
+}
+
+/// Build an m-of-n multisig script from the given public keys.
+pub fn multisig_script(threshold: usize, pubkeys: &[PublicKey]) -> Script {
+	let mut builder = Builder::new().push_int(threshold as i64);
+	for pk in pubkeys {
+		builder = builder.push_key(pk);
+	}
+	builder.push_int(pubkeys.len() as i64).push_opcode(OP_CHECKMULTISIG).into_script()
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct MultisigInfo {
+	pub threshold: usize,
+	pub pubkeys: Vec<PublicKey>,
+	pub script: HexBytes,
+	pub script_asm: String,
+	#[serde(flatten)]
+	pub addresses: Addresses,
+}
+
+impl MultisigInfo {
+	/// Create the multisig script and addresses for the given public keys.
+	///
+	/// When `sort` is set, the keys are sorted lexicographically as in BIP-67.
+	pub fn new(
+		threshold: usize,
+		pubkeys: &[PublicKey],
+		sort: bool,
+		blinder: Option<secp256k1::PublicKey>,
+		network: Network,
+	) -> MultisigInfo {
+		let mut pubkeys = pubkeys.to_vec();
+		if sort {
+			pubkeys.sort_by_key(|pk| pk.to_bytes());
+		}
+		let script = multisig_script(threshold, &pubkeys);
+		MultisigInfo {
+			threshold,
+			pubkeys,
+			script: script.to_bytes().into(),
+			script_asm: script.asm(),
+			addresses: Addresses::from_script(&script, blinder, network),
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use elements::bech32::{u5, ToBase32, Variant};
+
+	const LIQUID_P2WPKH: &str = "ex1qdzxn64mfz4agldtff5cckj7c4n87ugwz0za6sy";
+	const LIQUID_CONFIDENTIAL: &str = "lq1qqt9g5wct02et850xh8207j6affwpraan70recr0m9w266x5vfjxh56yd84tkj9t6376kjnf33d9a3tx0acsuy8xtwe5ay0u9d";
+
+	/// Encode a segwit address with the given version, program and checksum variant.
+	fn segwit(hrp: &str, version: u8, program: &[u8], variant: Variant) -> String {
+		let mut data = vec![u5::try_from_u8(version).unwrap()];
+		data.extend(program.to_base32());
+		bech32::encode(hrp, data, variant).unwrap()
+	}
+
+	fn diagnose(s: &str, expected: Option<Network>) -> AddressParseDiagnostics {
+		parse_address_diagnosed(s, expected).unwrap_err()
+	}
+
+	#[test]
+	fn parse_valid() {
+		let address = parse_address_diagnosed(LIQUID_P2WPKH, None).unwrap();
+		assert_eq!(address.params, &elements::AddressParams::LIQUID);
+		assert!(parse_address_diagnosed(LIQUID_CONFIDENTIAL, Some(Network::Liquid)).is_ok());
+		assert!(parse_address_diagnosed(&LIQUID_P2WPKH.to_uppercase(), None).is_ok());
+	}
+
+	#[test]
+	fn diagnose_wrong_network() {
+		let diag = diagnose(LIQUID_P2WPKH, Some(Network::LiquidTestnet));
+		assert_eq!(diag.failure, AddressParseFailure::WrongNetwork);
+		assert_eq!(diag.encoding.unwrap(), "bech32");
+		assert_eq!(diag.likely_network, Some(Network::Liquid));
+
+		let diag = diagnose(LIQUID_CONFIDENTIAL, Some(Network::ElementsRegtest));
+		assert_eq!(diag.failure, AddressParseFailure::WrongNetwork);
+		assert_eq!(diag.encoding.unwrap(), "blech32");
+	}
+
+	#[test]
+	fn diagnose_bitcoin_address() {
+		for s in &["bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2"] {
+			let diag = diagnose(s, None);
+			assert_eq!(diag.failure, AddressParseFailure::BitcoinAddress);
+			assert_eq!(diag.bitcoin_network, Some(bitcoin::Network::Bitcoin));
+			assert!(diag.matching_networks.is_empty());
+		}
+	}
+
+	#[test]
+	fn diagnose_checksum() {
+		let diag = diagnose("ex1qdzxn64mfz4agldtff5cckj7c4n87ugwz0za6sx", Some(Network::Liquid));
+		assert_eq!(diag.failure, AddressParseFailure::Checksum);
+		assert_eq!(diag.matching_networks, vec![Network::Liquid]);
+		assert_eq!(diag.likely_network, Some(Network::Liquid));
+
+		// The HRP is taken up to the last separator, the data part has none.
+		let mut s = LIQUID_CONFIDENTIAL.to_owned();
+		s.pop();
+		s.push('e');
+		let diag = diagnose(&s, None);
+		assert_eq!(diag.failure, AddressParseFailure::Checksum);
+		assert_eq!(diag.encoding.unwrap(), "blech32");
+		assert_eq!(diag.matching_networks, vec![Network::Liquid]);
+	}
+
+	#[test]
+	fn diagnose_unknown_prefix() {
+		let diag = diagnose(&segwit("tst", 0, &[1; 20], Variant::Bech32), None);
+		assert_eq!(diag.failure, AddressParseFailure::UnknownPrefix);
+		assert_eq!(diag.encoding.unwrap(), "bech32");
+		assert!(diag.matching_networks.is_empty());
+
+		let mut data = vec![200];
+		data.extend_from_slice(&[1; 20]);
+		let diag = diagnose(&base58::check_encode_slice(&data), None);
+		assert_eq!(diag.failure, AddressParseFailure::UnknownPrefix);
+		assert_eq!(diag.encoding.unwrap(), "base58");
+	}
+
+	#[test]
+	fn diagnose_custom_network() {
+		let params = Box::leak(Box::new(elements::AddressParams {
+			p2pkh_prefix: 36,
+			p2sh_prefix: 37,
+			blinded_prefix: 38,
+			bech_hrp: "tst",
+			blech_hrp: "tstb",
+		}));
+		let custom = Network::Custom(params);
+		let s = segwit("tst", 0, &[1; 20], Variant::Bech32);
+		assert!(parse_address_diagnosed(&s, Some(custom)).is_ok());
+
+		let mut invalid = s.clone();
+		invalid.pop();
+		invalid.push(if s.ends_with('q') { 'p' } else { 'q' });
+		let diag = diagnose(&invalid, Some(custom));
+		assert_eq!(diag.failure, AddressParseFailure::Checksum);
+		assert_eq!(diag.likely_network, Some(custom));
+	}
+
+	#[test]
+	fn diagnose_invalid_encoding() {
+		let cases = [
+			("ex1qdzxn64mfz4agldtff5cckj7c4n87ugwz0za6sb".to_owned(), AddressParseFailure::InvalidCharacter),
+			("ex1qdzxn64mfz4agldtff5cckj7c4n87ugwz0za6SY".to_owned(), AddressParseFailure::InvalidCharacter),
+			("hello".to_owned(), AddressParseFailure::InvalidCharacter),
+			(segwit("ex", 0, &[1; 10], Variant::Bech32), AddressParseFailure::Length),
+			(segwit("ex", 1, &[1; 1], Variant::Bech32m), AddressParseFailure::Length),
+			(base58::check_encode_slice(&[57, 1, 2]), AddressParseFailure::Length),
+			(segwit("ex", 17, &[1; 20], Variant::Bech32m), AddressParseFailure::WitnessVersion),
+			(segwit("ex", 1, &[1; 32], Variant::Bech32), AddressParseFailure::EncodingVariant),
+			(segwit("ex", 0, &[1; 20], Variant::Bech32m), AddressParseFailure::EncodingVariant),
+		];
+		for &(ref s, failure) in cases.iter() {
+			assert_eq!(diagnose(s, None).failure, failure, "{}", s);
+		}
+	}
+
+	#[test]
+	fn diagnose_blinding_pubkey() {
+		// A blinded elementsregtest p2pkh address with an invalid blinding pubkey.
+		let mut data = vec![4, 235, 2];
+		data.extend_from_slice(&[0xff; 32]);
+		data.extend_from_slice(&[1; 20]);
+		let diag = diagnose(&base58::check_encode_slice(&data), None);
+		assert_eq!(diag.failure, AddressParseFailure::BlindingPubkey);
+		assert_eq!(diag.likely_network, Some(Network::ElementsRegtest));
+	}
 }
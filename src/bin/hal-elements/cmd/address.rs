@@ -1,24 +1,33 @@
-use bitcoin::hashes::Hash;
-use elements::{Address, WPubkeyHash, WScriptHash};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use elements::Script;
+use elements::taproot::{TapBranchHash, TaprootSpendInfo};
 use bitcoin::{secp256k1, PublicKey};
 use clap;
+use serde::Serialize;
 
 use cmd;
-use hal;
 use hex;
+use hal_elements::address::{parse_address_diagnosed, AddressInfo, AddressParseDiagnostics};
 
-use ::{Network};
+use ::{Chain, Network};
 
 pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand_group("address", "work with addresses")
 		.subcommand(cmd_create())
 		.subcommand(cmd_inspect())
+		.subcommand(cmd_convert())
+		.subcommand(cmd_pegin())
 }
 
 pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
 	match matches.subcommand() {
 		("create", Some(ref m)) => exec_create(&m),
 		("inspect", Some(ref m)) => exec_inspect(&m),
+		("convert", Some(ref m)) => exec_convert(&m),
+		("pegin", Some(ref m)) => exec_pegin(&m),
 		(_, _) => unreachable!("clap prints help"),
 	};
 }
@@ -26,12 +35,79 @@ pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
 fn cmd_create<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand("create", "create addresses").args(&cmd::opts_networks()).args(&[
 		cmd::opt_yaml(),
-		cmd::opt("pubkey", "a public key in hex").takes_value(true).required(false),
+		cmd::opt("pubkey", "a public key in hex (can be used multiple times with --multisig)")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1)
+			.required(false),
+		cmd::opt("multisig", "create m-of-n multisig addresses with the given threshold m")
+			.takes_value(true)
+			.required(false)
+			.requires("pubkey"),
+		cmd::opt("sort", "sort the multisig pubkeys as in BIP-67")
+			.takes_value(false)
+			.required(false)
+			.requires("multisig"),
 		cmd::opt("script", "a script in hex").takes_value(true).required(false),
+		cmd::opt("internal-key", "a taproot internal key in hex")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with_all(&["pubkey", "script"]),
+		cmd::opt("merkle-root", "the taproot script tree merkle root in hex")
+			.takes_value(true)
+			.required(false)
+			.requires("internal-key")
+			.conflicts_with_all(&["pubkey", "script", "tapleaf"]),
+		cmd::opt("tapleaf", "a tapscript leaf script in hex (can be used multiple times, \
+			the leaves are put in a huffman tree with equal weights; use --merkle-root for other trees)")
+			.takes_value(true)
+			.multiple(true)
+			.number_of_values(1)
+			.required(false)
+			.requires("internal-key")
+			.conflicts_with_all(&["pubkey", "script"]),
 		cmd::opt("blinder", "a blinding pubkey in hex").takes_value(true).required(false),
+		cmd::opt("slip77-key", "a SLIP-77 master blinding key in hex to derive blinding keys")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with("blinder"),
 	])
 }
 
+/// Parse a taproot internal key, either in x-only or regular pubkey format.
+fn parse_internal_key(s: &str) -> secp256k1::XOnlyPublicKey {
+	if let Ok(key) = s.parse() {
+		key
+	} else {
+		let pubkey: secp256k1::PublicKey = s.parse().expect("invalid internal key");
+		pubkey.x_only_public_key().0
+	}
+}
+
+/// Get the taproot merkle root from either the merkle root or the tapleaf arguments.
+///
+/// All tapleaves get the same weight, so the tree is the one
+/// [TaprootSpendInfo::with_huffman_tree] builds for equally likely leaves.
+fn taproot_merkle_root<'a>(
+	matches: &clap::ArgMatches<'a>,
+	internal_key: secp256k1::XOnlyPublicKey,
+) -> Option<TapBranchHash> {
+	if let Some(root) = matches.value_of("merkle-root") {
+		Some(root.parse().expect("invalid merkle root"))
+	} else if let Some(leaves) = matches.values_of("tapleaf") {
+		let leaves = leaves.map(|l| {
+			let script: Script = hex::decode(l).expect("invalid tapleaf script hex").into();
+			(1, script)
+		});
+		let secp = secp256k1::Secp256k1::verification_only();
+		TaprootSpendInfo::with_huffman_tree(&secp, internal_key, leaves)
+			.expect("invalid taproot script tree")
+			.merkle_root()
+	} else {
+		None
+	}
+}
+
 fn exec_create<'a>(matches: &clap::ArgMatches<'a>) {
 	let network = cmd::network(matches);
 
@@ -40,7 +116,38 @@ fn exec_create<'a>(matches: &clap::ArgMatches<'a>) {
 		secp256k1::PublicKey::from_slice(&bytes).expect("invalid blinder")
 	});
 
-	let created = if let Some(pubkey_hex) = matches.value_of("pubkey") {
+	if let Some(threshold) = matches.value_of("multisig") {
+		let threshold: usize = threshold.parse().expect("invalid multisig threshold");
+		let pubkeys = matches
+			.values_of("pubkey")
+			.expect("multisig requires pubkeys")
+			.map(|pk| pk.parse().expect("invalid pubkey"))
+			.collect::<Vec<PublicKey>>();
+		if threshold == 0 || threshold > pubkeys.len() {
+			panic!("multisig threshold must be between 1 and the number of pubkeys");
+		}
+		if pubkeys.len() > 20 {
+			panic!("multisig supports at most 20 pubkeys");
+		}
+
+		let mut info = hal_elements::address::MultisigInfo::new(
+			threshold,
+			&pubkeys,
+			matches.is_present("sort"),
+			blinder,
+			network,
+		);
+		if let Some(key_hex) = matches.value_of("slip77-key") {
+			info.addresses.blind_slip77(&cmd::parse_slip77_key(key_hex));
+		}
+		return cmd::print_output(matches, &info);
+	}
+
+	if matches.occurrences_of("pubkey") > 1 {
+		panic!("multiple pubkeys can only be used with --multisig");
+	}
+
+	let mut created = if let Some(pubkey_hex) = matches.value_of("pubkey") {
 		let pubkey: PublicKey = pubkey_hex.parse().expect("invalid pubkey");
 		hal_elements::address::Addresses::from_pubkey(&pubkey, blinder, network)
 	} else if let Some(script_hex) = matches.value_of("script") {
@@ -48,83 +155,171 @@ fn exec_create<'a>(matches: &clap::ArgMatches<'a>) {
 		let script = script_bytes.into();
 
 		hal_elements::address::Addresses::from_script(&script, blinder, network)
+	} else if let Some(key_hex) = matches.value_of("internal-key") {
+		let internal_key = parse_internal_key(key_hex);
+		let merkle_root = taproot_merkle_root(matches, internal_key);
+
+		hal_elements::address::Addresses::from_internal_key(internal_key, merkle_root, blinder, network)
 	} else {
 		panic!("Can't create addresses without a pubkey");
 	};
 
+	if let Some(key_hex) = matches.value_of("slip77-key") {
+		created.blind_slip77(&cmd::parse_slip77_key(key_hex));
+	}
+
 	cmd::print_output(matches, &created)
 }
 
 fn cmd_inspect<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand("inspect", "inspect addresses")
-		.args(&[cmd::opt_yaml(), cmd::arg("address", "the address").required(true)])
+		.args(&cmd::opts_networks())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("address", "the address").required_unless("batch"),
+			cmd::opt("batch", "inspect one address per line from stdin and output one JSON object per line")
+				.takes_value(false)
+				.required(false)
+				.conflicts_with_all(&["address", "yaml"]),
+			cmd::opt("file", "read the batch of addresses from a file instead of stdin")
+				.takes_value(true)
+				.required(false)
+				.requires("batch"),
+		])
 }
 
-fn exec_inspect<'a>(matches: &clap::ArgMatches<'a>) {
-	let address_str = matches.value_of("address").expect("no address provided");
-	let address: Address = address_str.parse().expect("invalid address format");
-	let script_pk = address.script_pubkey();
-
-	let mut info = hal_elements::address::AddressInfo {
-		network: Network::from_params(address.params).expect("addresses always have params"),
-		script_pub_key: hal::tx::OutputScriptInfo {
-			hex: Some(script_pk.to_bytes().into()),
-			asm: Some(script_pk.asm()),
-			address: None,
-			type_: None,
-		},
-		type_: None,
-		pubkey_hash: None,
-		script_hash: None,
-		witness_pubkey_hash: None,
-		witness_script_hash: None,
-		witness_program_version: None,
-		blinding_pubkey: address.blinding_pubkey,
-		unconfidential: if address.blinding_pubkey.is_some() {
-			Some(Address {
-				params: address.params,
-				payload: address.payload.clone(),
-				blinding_pubkey: None,
-			})
-		} else {
-			None
-		},
+/// A single line of output of a batch address inspection.
+#[derive(Serialize)]
+struct BatchInspectEntry<'a> {
+	line: usize,
+	address: &'a str,
+	#[serde(flatten, skip_serializing_if = "Option::is_none")]
+	info: Option<AddressInfo>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error: Option<AddressParseDiagnostics>,
+}
+
+fn exec_inspect_batch<'a>(matches: &clap::ArgMatches<'a>, chain: Chain<'static>, expected: Option<Network>) {
+	let input: Box<dyn BufRead> = match matches.value_of("file") {
+		Some(path) => Box::new(io::BufReader::new(fs::File::open(path).expect("failed to open file"))),
+		None => Box::new(io::BufReader::new(io::stdin())),
 	};
 
-	use elements::address::Payload;
-	match address.payload {
-		Payload::PubkeyHash(pkh) => {
-			info.type_ = Some("p2pkh".to_owned());
-			info.pubkey_hash = Some(pkh);
+	let stdout = io::stdout();
+	let mut stdout = stdout.lock();
+	for (idx, line) in input.lines().enumerate() {
+		let line = line.expect("failed to read input");
+		let address_str = line.trim();
+		if address_str.is_empty() {
+			continue;
 		}
-		Payload::ScriptHash(sh) => {
-			info.type_ = Some("p2sh".to_owned());
-			info.script_hash = Some(sh);
+
+		let entry = match parse_address_diagnosed(address_str, expected) {
+			Ok(address) => BatchInspectEntry {
+				line: idx + 1,
+				address: address_str,
+				info: Some(::GetInfo::get_info(&address, chain)),
+				error: None,
+			},
+			Err(e) => BatchInspectEntry {
+				line: idx + 1,
+				address: address_str,
+				info: None,
+				error: Some(e),
+			},
+		};
+		serde_json::to_writer(&mut stdout, &entry).unwrap();
+		writeln!(stdout).unwrap();
+	}
+}
+
+fn exec_inspect<'a>(matches: &clap::ArgMatches<'a>) {
+	// Only check the network when one was given explicitly.
+	let chain = cmd::chain(matches);
+	let expected = cmd::explicit_network(matches, chain.network);
+
+	if matches.is_present("batch") {
+		return exec_inspect_batch(matches, chain, expected);
+	}
+
+	let address_str = matches.value_of("address").expect("no address provided");
+	let address = match parse_address_diagnosed(address_str, expected) {
+		Ok(address) => address,
+		Err(diag) => {
+			cmd::print_output(matches, &diag);
+			process::exit(1);
 		}
-		Payload::WitnessProgram {
-			version,
-			program,
-		} => {
-			let version = version.to_u8() as usize;
-			info.witness_program_version = Some(version);
-
-			if version == 0 {
-				if program.len() == 20 {
-					info.type_ = Some("p2wpkh".to_owned());
-					info.witness_pubkey_hash = 
-						Some(WPubkeyHash::from_slice(&program).expect("size 20"));
-				} else if program.len() == 32 {
-					info.type_ = Some("p2wsh".to_owned());
-					info.witness_script_hash =
-						Some(WScriptHash::from_slice(&program).expect("size 32"));
-				} else {
-					info.type_ = Some("invalid-witness-program".to_owned());
-				}
-			} else {
-				info.type_ = Some("unknown-witness-program-version".to_owned());
-			}
+	};
+	let info = ::GetInfo::get_info(&address, chain);
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_convert<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("convert", "convert addresses between confidential and unconfidential form and between networks")
+		.args(&cmd::opts_networks())
+		.args(&[
+			cmd::arg("address", "the address").required(true),
+			cmd::opt("blinder", "a blinding pubkey in hex to make the address confidential")
+				.takes_value(true)
+				.required(false),
+			cmd::opt("slip77-key", "a SLIP-77 master blinding key in hex to make the address confidential")
+				.takes_value(true)
+				.required(false)
+				.conflicts_with("blinder"),
+			cmd::opt("unconfidential", "remove the blinding pubkey from the address")
+				.takes_value(false)
+				.required(false)
+				.conflicts_with_all(&["blinder", "slip77-key"]),
+		])
+}
+
+fn exec_convert<'a>(matches: &clap::ArgMatches<'a>) {
+	// Only convert network when one was given explicitly.
+	let network = cmd::explicit_network(matches, cmd::network(matches));
+
+	// Accept addresses of any network, they are converted below.
+	let address_str = matches.value_of("address").expect("no address provided");
+	let mut address = match parse_address_diagnosed(address_str, None) {
+		Ok(address) => address,
+		Err(diag) => {
+			cmd::print_output(matches, &diag);
+			process::exit(1);
 		}
+	};
+
+	if let Some(network) = network {
+		address.params = network.address_params();
+	}
+
+	if matches.is_present("unconfidential") {
+		address = address.to_unconfidential();
+	} else if let Some(blinder_hex) = matches.value_of("blinder") {
+		let bytes = hex::decode(blinder_hex).expect("invaid blinder hex");
+		let blinder = secp256k1::PublicKey::from_slice(&bytes).expect("invalid blinder");
+		address = address.to_confidential(blinder);
+	} else if let Some(key_hex) = matches.value_of("slip77-key") {
+		let master_key = cmd::parse_slip77_key(key_hex);
+		let blinding_key = master_key.derive_blinding_key(&address.script_pubkey());
+		let secp = secp256k1::Secp256k1::signing_only();
+		address = address.to_confidential(secp256k1::PublicKey::from_secret_key(&secp, &blinding_key));
 	}
 
+	println!("{}", address);
+}
+
+fn cmd_pegin<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("pegin", "create a mainchain peg-in deposit address")
+		.args(&cmd::opts_networks())
+		.args(&cmd::opts_pegin())
+		.args(&[cmd::opt_yaml()])
+}
+
+fn exec_pegin<'a>(matches: &clap::ArgMatches<'a>) {
+	let network = cmd::network(matches);
+	let info = hal_elements::pegin::pegin_address(
+		&cmd::fedpeg_script(matches),
+		&cmd::claim_script(matches),
+		cmd::mainchain_network(matches, network),
+	).unwrap_or_else(|e| panic!("{}", e));
 	cmd::print_output(matches, &info)
 }
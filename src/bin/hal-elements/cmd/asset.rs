@@ -0,0 +1,129 @@
+use clap;
+use elements::encode::deserialize;
+use elements::bitcoin::hashes::Hash;
+use elements::{ContractHash, OutPoint, Transaction};
+
+use cmd;
+use hal_elements::tx::{contract_hash, is_reissuance, verify_issuance_contract, IssuanceIdsInfo};
+
+pub fn subcommand<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand_group("asset", "calculate issued assets and their contract hashes")
+		.subcommand(cmd_contract_hash())
+		.subcommand(cmd_issuance())
+		.subcommand(cmd_verify_contract())
+}
+
+pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
+	match matches.subcommand() {
+		("contract-hash", Some(ref m)) => exec_contract_hash(&m),
+		("issuance", Some(ref m)) => exec_issuance(&m),
+		("verify-contract", Some(ref m)) => exec_verify_contract(&m),
+		(_, _) => unreachable!("clap prints help"),
+	};
+}
+
+fn opts_contract<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	vec![
+		cmd::opt("contract", "the asset contract in JSON").takes_value(true).required(false),
+		cmd::opt("contract-hash", "the contract hash in hex, byte-reversed like txids")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with("contract"),
+	]
+}
+
+/// Get the contract hash from either the contract or the contract hash option.
+fn contract_hash_opt<'a>(matches: &clap::ArgMatches<'a>) -> Option<ContractHash> {
+	if let Some(contract) = matches.value_of("contract") {
+		Some(contract_hash(contract).unwrap_or_else(|e| panic!("{}", e)))
+	} else {
+		matches.value_of("contract-hash").map(|h| h.parse().expect("invalid contract hash"))
+	}
+}
+
+fn cmd_contract_hash<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("contract-hash", "calculate the contract hash of a JSON asset contract")
+		.after_help(
+			"The contract hash is the SHA-256 of the contract with its keys sorted and is \
+			printed byte-reversed, like txids and the contract_hash of decoded issuances. \
+			The asset_entropy field of a raw new issuance holds it in the unreversed order.",
+		)
+		.args(&[
+			cmd::arg("contract", "the asset contract in JSON").required(false),
+		])
+}
+
+fn exec_contract_hash<'a>(matches: &clap::ArgMatches<'a>) {
+	let contract = cmd::arg_or_stdin(matches, "contract");
+	let hash = contract_hash(&contract).unwrap_or_else(|e| panic!("{}", e));
+	print!("{}", hash);
+}
+
+fn cmd_issuance<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("issuance", "calculate the entropy, asset ID and token ID of an issuance")
+		.args(&opts_contract())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::opt("outpoint", "the outpoint spent by the issuing input, like <txid>:<vout>")
+				.takes_value(true)
+				.required_unless("entropy"),
+			cmd::opt("entropy", "the asset entropy of an earlier issuance")
+				.takes_value(true)
+				.required(false)
+				.conflicts_with_all(&["outpoint", "contract", "contract-hash"]),
+			cmd::opt("confidential", "the issuance amount is confidential")
+				.takes_value(false)
+				.required(false),
+		])
+}
+
+fn exec_issuance<'a>(matches: &clap::ArgMatches<'a>) {
+	let confidential = matches.is_present("confidential");
+
+	let info = if let Some(entropy) = matches.value_of("entropy") {
+		IssuanceIdsInfo::from_entropy(entropy.parse().expect("invalid entropy"), confidential)
+	} else {
+		let outpoint: OutPoint =
+			matches.value_of("outpoint").unwrap().parse().expect("invalid outpoint");
+		// Issuances without a contract commit to the zero hash.
+		let contract_hash = contract_hash_opt(matches)
+			.unwrap_or_else(|| ContractHash::from_inner([0; 32]));
+		IssuanceIdsInfo::from_outpoint(outpoint, contract_hash, confidential)
+	};
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_verify_contract<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("verify-contract", "verify that the issuance in a transaction commits to a contract")
+		.args(&opts_contract())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt("input", "the index of the issuing input (default: the only issuing input)")
+				.takes_value(true)
+				.required(false),
+		])
+}
+
+fn exec_verify_contract<'a>(matches: &clap::ArgMatches<'a>) {
+	let contract_hash = contract_hash_opt(matches).expect("either contract or contract-hash is required");
+	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
+	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
+	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
+
+	let input = if let Some(i) = matches.value_of("input") {
+		i.parse().expect("invalid input index")
+	} else {
+		let issuing = (0..tx.input.len())
+			.filter(|i| tx.input[*i].has_issuance() && !is_reissuance(&tx.input[*i]))
+			.collect::<Vec<_>>();
+		match issuing.len() {
+			0 => panic!("transaction has no new issuances"),
+			1 => issuing[0],
+			_ => panic!("transaction has multiple new issuances, select one with --input"),
+		}
+	};
+
+	let info = verify_issuance_contract(&tx, input, contract_hash).unwrap_or_else(|e| panic!("{}", e));
+	cmd::print_output(matches, &info)
+}
@@ -0,0 +1,147 @@
+use std::str::FromStr;
+
+use bitcoin::{secp256k1, PublicKey};
+use bitcoin::util::bip32;
+use clap;
+
+use cmd;
+use hal_elements::address::Addresses;
+use hal_elements::bip32::DerivationInfo;
+
+use ::Network;
+
+pub fn subcommand<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand_group("bip32", "BIP-32 key derivation")
+		.subcommand(cmd_derive())
+		.subcommand(cmd_inspect())
+}
+
+pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
+	match matches.subcommand() {
+		("derive", Some(ref m)) => exec_derive(&m),
+		("inspect", Some(ref m)) => exec_inspect(&m),
+		(_, _) => unreachable!("clap prints help"),
+	};
+}
+
+/// Options to blind the addresses of derived keys.
+fn opts_blinding<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	vec![
+		cmd::opt("blinder", "a blinding pubkey in hex").takes_value(true).required(false),
+		cmd::opt("slip77-key", "a SLIP-77 master blinding key in hex to derive blinding keys")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with("blinder"),
+	]
+}
+
+/// Create the addresses for the public key, blinded if requested.
+fn create_addresses<'a>(
+	matches: &clap::ArgMatches<'a>,
+	network: Network,
+	pubkey: secp256k1::PublicKey,
+) -> Addresses {
+	let blinder = matches.value_of("blinder").map(|b| {
+		let bytes = hex::decode(b).expect("invaid blinder hex");
+		secp256k1::PublicKey::from_slice(&bytes).expect("invalid blinder")
+	});
+
+	let mut addresses =
+		Addresses::from_pubkey(&PublicKey::new(pubkey), blinder, network);
+	if let Some(key_hex) = matches.value_of("slip77-key") {
+		addresses.blind_slip77(&cmd::parse_slip77_key(key_hex));
+	}
+	addresses
+}
+
+fn cmd_derive<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("derive", "derive keys from an extended key")
+		.args(&cmd::opts_networks())
+		.args(&opts_blinding())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("ext-key", "extended public or private key").required(true),
+			cmd::arg("derivation-path", "the derivation path").required(true),
+		])
+}
+
+fn exec_derive<'a>(matches: &clap::ArgMatches<'a>) {
+	let path_str = matches.value_of("derivation-path").unwrap();
+	let path: bip32::DerivationPath = path_str.parse().expect("error parsing derivation path");
+	let key_str = matches.value_of("ext-key").unwrap();
+
+	let network = cmd::network(matches);
+	let secp = secp256k1::Secp256k1::new();
+
+	let master_fingerprint;
+	let mut derived_xpriv = None;
+	let derived_xpub = match bip32::ExtendedPrivKey::from_str(key_str) {
+		Ok(ext_priv) => {
+			derived_xpriv = Some(ext_priv.derive_priv(&secp, &path).expect("derivation error"));
+			master_fingerprint = ext_priv.fingerprint(&secp);
+			bip32::ExtendedPubKey::from_priv(&secp, derived_xpriv.as_ref().unwrap())
+		}
+		Err(_) => {
+			let ext_pub: bip32::ExtendedPubKey = key_str.parse().expect("invalid extended key");
+			master_fingerprint = ext_pub.fingerprint();
+			ext_pub.derive_pub(&secp, &path).expect("derivation error")
+		}
+	};
+
+	let info = DerivationInfo {
+		network,
+		master_fingerprint: Some(master_fingerprint),
+		path: Some(path),
+		xpriv: derived_xpriv,
+		xpub: derived_xpub,
+		chain_code: derived_xpub.chain_code,
+		identifier: derived_xpub.identifier(),
+		fingerprint: derived_xpub.fingerprint(),
+		public_key: derived_xpub.public_key,
+		private_key: derived_xpriv.map(|x| x.private_key),
+		parent_fingerprint: derived_xpub.parent_fingerprint,
+		addresses: create_addresses(matches, network, derived_xpub.public_key),
+	};
+
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_inspect<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("inspect", "inspect a BIP-32 xpub or xpriv")
+		.args(&cmd::opts_networks())
+		.args(&opts_blinding())
+		.args(&[cmd::opt_yaml(), cmd::arg("ext-key", "extended public or private key").required(true)])
+}
+
+fn exec_inspect<'a>(matches: &clap::ArgMatches<'a>) {
+	let key_str = matches.value_of("ext-key").unwrap();
+
+	let network = cmd::network(matches);
+	let secp = secp256k1::Secp256k1::signing_only();
+
+	let mut xpriv = None;
+	let xpub = match bip32::ExtendedPrivKey::from_str(key_str) {
+		Ok(ext_priv) => {
+			xpriv = Some(ext_priv);
+			bip32::ExtendedPubKey::from_priv(&secp, xpriv.as_ref().unwrap())
+		}
+		Err(_) => key_str.parse().expect("invalid extended key"),
+	};
+
+	let info = DerivationInfo {
+		network,
+		master_fingerprint: None,
+		path: None,
+		xpriv,
+		xpub,
+		chain_code: xpub.chain_code,
+		identifier: xpub.identifier(),
+		fingerprint: xpub.fingerprint(),
+		public_key: xpub.public_key,
+		private_key: xpriv.map(|x| x.private_key),
+		parent_fingerprint: xpub.parent_fingerprint,
+		addresses: create_addresses(matches, network, xpub.public_key),
+	};
+
+	cmd::print_output(matches, &info)
+}
@@ -144,7 +144,7 @@ fn exec_decode<'a>(matches: &clap::ArgMatches<'a>) {
 	if matches.is_present("txids") {
 		let block: Block = deserialize(&raw_tx).expect("invalid block format");
 		let info = BlockInfo {
-			header: ::GetInfo::get_info(&block.header, cmd::network(matches)),
+			header: ::GetInfo::get_info(&block.header, cmd::chain(matches)),
 			txids: Some(block.txdata.iter().map(|t| t.txid()).collect()),
 			transactions: None,
 			raw_transactions: None,
@@ -158,7 +158,7 @@ fn exec_decode<'a>(matches: &clap::ArgMatches<'a>) {
 				block.header
 			}
 		};
-		let info = ::GetInfo::get_info(&header, cmd::network(matches));
+		let info = ::GetInfo::get_info(&header, cmd::chain(matches));
 		cmd::print_output(matches, &info)
 	}
 }
@@ -0,0 +1,107 @@
+use clap;
+use elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
+use elements::secp256k1_zkp::{Generator, PedersenCommitment};
+
+use cmd;
+use hal_elements::confidential::{asset_commitment, value_commitment, CommitmentOpeningInfo};
+
+pub fn subcommand<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand_group("confidential", "compute and verify asset and value commitments")
+		.subcommand(cmd_asset_commitment())
+		.subcommand(cmd_value_commitment())
+		.subcommand(cmd_open())
+}
+
+pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
+	match matches.subcommand() {
+		("asset-commitment", Some(ref m)) => exec_asset_commitment(&m),
+		("value-commitment", Some(ref m)) => exec_value_commitment(&m),
+		("open", Some(ref m)) => exec_open(&m),
+		(_, _) => unreachable!("clap prints help"),
+	};
+}
+
+fn parse_abf(s: Option<&str>) -> AssetBlindingFactor {
+	s.map(|s| s.parse().expect("invalid asset blinding factor")).unwrap_or_else(AssetBlindingFactor::zero)
+}
+
+fn parse_vbf(s: Option<&str>) -> ValueBlindingFactor {
+	s.map(|s| s.parse().expect("invalid value blinding factor")).unwrap_or_else(ValueBlindingFactor::zero)
+}
+
+fn cmd_asset_commitment<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("asset-commitment", "compute the asset commitment (generator) of an asset").args(&[
+		cmd::arg("asset", "the asset ID").required(true),
+		cmd::arg("asset-blinding-factor", "the asset blinding factor (default: unblinded)").required(false),
+	])
+}
+
+fn exec_asset_commitment<'a>(matches: &clap::ArgMatches<'a>) {
+	let asset = matches.value_of("asset").unwrap().parse().expect("invalid asset ID");
+	let abf = parse_abf(matches.value_of("asset-blinding-factor"));
+
+	print!("{}", hex::encode(&asset_commitment(asset, abf).serialize()[..]));
+}
+
+fn cmd_value_commitment<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("value-commitment", "compute the value commitment of a value").args(&[
+		cmd::arg("value", "the value in satoshis").required(true),
+		cmd::arg("asset-commitment", "the asset commitment, or an asset ID for an unblinded asset")
+			.required(true),
+		cmd::arg("value-blinding-factor", "the value blinding factor (default: unblinded)").required(false),
+	])
+}
+
+fn exec_value_commitment<'a>(matches: &clap::ArgMatches<'a>) {
+	let value = matches.value_of("value").unwrap().parse().expect("invalid value");
+	let generator = cmd::parse_asset_commitment(matches.value_of("asset-commitment").unwrap());
+	let vbf = parse_vbf(matches.value_of("value-blinding-factor"));
+
+	print!("{}", hex::encode(&value_commitment(value, generator, vbf).serialize()[..]));
+}
+
+fn cmd_open<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("open", "open commitments with the claimed secrets and verify them").args(&[
+		cmd::opt_yaml(),
+		cmd::opt("asset", "the claimed asset ID").takes_value(true).required(true),
+		cmd::opt("asset-blinding-factor", "the claimed asset blinding factor (default: unblinded)")
+			.takes_value(true)
+			.required(false),
+		cmd::opt("asset-commitment", "the asset commitment to verify").takes_value(true).required(false),
+		cmd::opt("value", "the claimed value in satoshis").takes_value(true).required(false),
+		cmd::opt("value-blinding-factor", "the claimed value blinding factor (default: unblinded)")
+			.takes_value(true)
+			.required(false)
+			.requires("value"),
+		cmd::opt("value-commitment", "the value commitment to verify")
+			.takes_value(true)
+			.required(false)
+			.requires("value"),
+	])
+}
+
+fn exec_open<'a>(matches: &clap::ArgMatches<'a>) {
+	let asset = matches.value_of("asset").unwrap().parse().expect("invalid asset ID");
+	let abf = parse_abf(matches.value_of("asset-blinding-factor"));
+	let generator = asset_commitment(asset, abf);
+
+	let value_comm = matches.value_of("value").map(|v| {
+		let value = v.parse().expect("invalid value");
+		value_commitment(value, generator, parse_vbf(matches.value_of("value-blinding-factor")))
+	});
+
+	let info = CommitmentOpeningInfo {
+		asset_commitment: generator.serialize()[..].into(),
+		asset_commitment_valid: matches.value_of("asset-commitment").map(|c| {
+			let bytes = hex::decode(c).expect("invalid asset commitment hex");
+			Generator::from_slice(&bytes).expect("invalid asset commitment") == generator
+		}),
+		value_commitment: value_comm.map(|c| c.serialize()[..].into()),
+		value_commitment_valid: matches.value_of("value-commitment").map(|c| {
+			let bytes = hex::decode(c).expect("invalid value commitment hex");
+			let comm = PedersenCommitment::from_slice(&bytes).expect("invalid value commitment");
+			Some(comm) == value_comm
+		}),
+	};
+	cmd::print_output(matches, &info)
+}
@@ -0,0 +1,64 @@
+use clap;
+
+use cmd;
+use hal_elements::descriptor::ConfidentialDescriptor;
+
+pub fn subcommand<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand_group("descriptor", "work with confidential output descriptors")
+		.subcommand(cmd_derive())
+}
+
+pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
+	match matches.subcommand() {
+		("derive", Some(ref m)) => exec_derive(&m),
+		(_, _) => unreachable!("clap prints help"),
+	};
+}
+
+fn cmd_derive<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("derive", "derive confidential addresses from a descriptor")
+		.args(&cmd::opts_networks())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("descriptor", "the confidential descriptor, like ct(slip77(...),elwpkh(...))")
+				.required(false),
+			cmd::opt("index", "the unhardened derivation index (default: 0)")
+				.takes_value(true)
+				.required(false),
+			cmd::opt("range", "an inclusive range of derivation indices, like 0-9")
+				.takes_value(true)
+				.required(false)
+				.conflicts_with("index"),
+		])
+}
+
+fn exec_derive<'a>(matches: &clap::ArgMatches<'a>) {
+	let network = cmd::network(matches);
+	let descriptor: ConfidentialDescriptor = cmd::arg_or_stdin(matches, "descriptor")
+		.parse()
+		.unwrap_or_else(|e| panic!("invalid descriptor: {}", e));
+
+	let (start, end) = if let Some(range) = matches.value_of("range") {
+		let mut parts = range.splitn(2, '-');
+		let start: u32 = parts.next().unwrap().parse().expect("invalid range start");
+		let end: u32 = parts.next().expect("range must be like 0-9").parse().expect("invalid range end");
+		if end < start {
+			panic!("range end must not be smaller than range start");
+		}
+		(start, end)
+	} else {
+		let index = matches.value_of("index").map(|i| i.parse().expect("invalid index")).unwrap_or(0);
+		(index, index)
+	};
+	if end >= 1 << 31 {
+		panic!("derivation indices must be smaller than 2^31, hardened derivation is not supported");
+	}
+	if !descriptor.has_wildcard() && (start != 0 || end != 0) {
+		warn!("Descriptor has no wildcard, all indices give the same address.");
+	}
+
+	let infos = (start..=end)
+		.map(|i| descriptor.derive_info(i, network).unwrap_or_else(|e| panic!("{}", e)))
+		.collect::<Vec<_>>();
+	cmd::print_output(matches, &infos)
+}
@@ -1,18 +1,35 @@
 pub mod address;
+pub mod asset;
+pub mod bip32;
 pub mod block;
+pub mod confidential;
+pub mod descriptor;
 pub mod tx;
 
 use std::io;
+use std::fs;
 use std::borrow::Cow;
 use std::io::Read;
 
-use hal_elements::Network;
+use bitcoin;
+use elements::confidential::Asset;
+use elements::AddressParams;
+use elements::secp256k1_zkp::{Generator, SecretKey};
+use elements::slip77::MasterBlindingKey;
+
+use hal_elements::block::ParamsInfo;
+use hal_elements::registry::AssetRegistry;
+use hal_elements::{Chain, ChainParams, Network};
 
 /// Build a list of all built-in subcommands.
 pub fn subcommands<'a>() -> Vec<clap::App<'a, 'a>> {
 	vec![
 		address::subcommand(),
+		asset::subcommand(),
+		bip32::subcommand(),
 		block::subcommand(),
+		confidential::subcommand(),
+		descriptor::subcommand(),
 		tx::subcommand(),
 	]
 }
@@ -47,7 +64,7 @@ pub fn subcommand<'a>(name: &'static str, about: &'static str) -> clap::App<'a,
 }
 
 pub fn opts_networks<'a>() -> Vec<clap::Arg<'a, 'a>> {
-	vec![
+	let mut opts = vec![
 		clap::Arg::with_name("elementsregtest")
 			.long("elementsregtest")
 			.short("r")
@@ -59,19 +76,233 @@ pub fn opts_networks<'a>() -> Vec<clap::Arg<'a, 'a>> {
 			.help("run in liquid mode")
 			.takes_value(false)
 			.required(false),
+		clap::Arg::with_name("liquidtestnet")
+			.long("liquidtestnet")
+			.help("run in liquid testnet mode")
+			.takes_value(false)
+			.required(false),
+		opt("policy-asset", "the policy asset of a regtest or custom chain")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with_all(&["liquid", "liquidtestnet"]),
+	];
+	opts.extend(opts_custom_chain());
+	opts
+}
+
+/// Options to define the parameters of a custom chain.
+pub fn opts_custom_chain<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	vec![
+		opt("chain-profile", "a JSON file with custom chain parameters")
+			.takes_value(true)
+			.required(false),
+		opt("p2pkh-prefix", "the p2pkh base58 prefix of a custom chain")
+			.takes_value(true)
+			.required(false)
+			.requires_all(&["p2sh-prefix", "blinded-prefix", "bech-hrp", "blech-hrp"])
+			.conflicts_with("chain-profile"),
+		opt("p2sh-prefix", "the p2sh base58 prefix of a custom chain")
+			.takes_value(true)
+			.required(false)
+			.requires("p2pkh-prefix"),
+		opt("blinded-prefix", "the blinded base58 prefix of a custom chain")
+			.takes_value(true)
+			.required(false)
+			.requires("p2pkh-prefix"),
+		opt("bech-hrp", "the bech32 HRP of a custom chain")
+			.takes_value(true)
+			.required(false)
+			.requires("p2pkh-prefix"),
+		opt("blech-hrp", "the blech32 HRP of a custom chain")
+			.takes_value(true)
+			.required(false)
+			.requires("p2pkh-prefix"),
 	]
 }
 
-pub fn network<'a>(matches: &clap::ArgMatches<'a>) -> Network {
-	if matches.is_present("elementsregtest") {
+/// Load the custom chain parameters, if any are provided.
+pub fn custom_chain_params<'a>(matches: &clap::ArgMatches<'a>) -> Option<ChainParams> {
+	if let Some(path) = matches.value_of("chain-profile") {
+		let file = fs::File::open(path).expect("failed to open chain profile");
+		Some(serde_json::from_reader(file).expect("invalid chain profile"))
+	} else if let Some(p2pkh) = matches.value_of("p2pkh-prefix") {
+		let prefix = |name: &str| -> u8 {
+			matches.value_of(name).expect("all custom chain params are required")
+				.parse().unwrap_or_else(|_| panic!("invalid {}", name))
+		};
+		Some(ChainParams {
+			p2pkh_prefix: p2pkh.parse().expect("invalid p2pkh-prefix"),
+			p2sh_prefix: prefix("p2sh-prefix"),
+			blinded_prefix: prefix("blinded-prefix"),
+			bech_hrp: matches.value_of("bech-hrp").expect("bech-hrp is required").to_owned(),
+			blech_hrp: matches.value_of("blech-hrp").expect("blech-hrp is required").to_owned(),
+			policy_asset: None,
+		})
+	} else {
+		None
+	}
+}
+
+/// The address params of the custom chain.
+///
+/// Addresses refer to their params statically, so they are kept until the process
+/// exits. Resolve the chain once per command with [chain] to only do this once.
+fn custom_address_params(params: &ChainParams) -> &'static AddressParams {
+	Box::leak(Box::new(AddressParams {
+		p2pkh_prefix: params.p2pkh_prefix,
+		p2sh_prefix: params.p2sh_prefix,
+		blinded_prefix: params.blinded_prefix,
+		bech_hrp: Box::leak(params.bech_hrp.to_lowercase().into_boxed_str()),
+		blech_hrp: Box::leak(params.blech_hrp.to_lowercase().into_boxed_str()),
+	}))
+}
+
+/// The chain to describe objects for, with the policy asset of the custom
+/// chain or the one given explicitly.
+///
+/// This loads the custom chain, so call it once per command and pass the
+/// chain or its network along.
+pub fn chain<'a>(matches: &clap::ArgMatches<'a>) -> Chain<'static> {
+	let custom = custom_chain_params(matches);
+	let network = if let Some(ref params) = custom {
+		Network::Custom(custom_address_params(params))
+	} else if matches.is_present("elementsregtest") {
 		Network::ElementsRegtest
 	} else if matches.is_present("liquid") {
 		Network::Liquid
+	} else if matches.is_present("liquidtestnet") {
+		Network::LiquidTestnet
 	} else {
 		Network::ElementsRegtest
+	};
+
+	let mut chain = Chain::new(network);
+	if let Some(asset) = custom.and_then(|p| p.policy_asset) {
+		chain.policy_asset = Some(asset);
+	}
+	if let Some(asset) = matches.value_of("policy-asset") {
+		chain.policy_asset = Some(asset.parse().expect("invalid policy asset"));
+	}
+	chain
+}
+
+pub fn network<'a>(matches: &clap::ArgMatches<'a>) -> Network {
+	chain(matches).network
+}
+
+/// The network resolved by [chain], if it was given explicitly on the command line.
+pub fn explicit_network<'a>(matches: &clap::ArgMatches<'a>, network: Network) -> Option<Network> {
+	let given = matches.is_present("elementsregtest")
+		|| matches.is_present("liquid")
+		|| matches.is_present("liquidtestnet");
+	match network {
+		Network::Custom(_) => Some(network),
+		_ if given => Some(network),
+		_ => None,
+	}
+}
+
+pub fn opt_asset_registry<'a>() -> clap::Arg<'a, 'a> {
+	opt("asset-registry", "a local asset registry file in the Blockstream registry JSON format")
+		.takes_value(true)
+		.required(false)
+}
+
+/// Load the asset registry, if one is provided.
+pub fn asset_registry<'a>(matches: &clap::ArgMatches<'a>) -> Option<AssetRegistry> {
+	matches.value_of("asset-registry").map(|path| {
+		let json = fs::read_to_string(path).expect("failed to read asset registry file");
+		AssetRegistry::from_json(&json).unwrap_or_else(|e| panic!("invalid asset registry: {}", e))
+	})
+}
+
+/// The options to select the fedpegscript of the sidechain.
+pub fn opts_fedpeg<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	vec![
+		opt("fedpeg-script", "the fedpegscript of the sidechain in hex")
+			.takes_value(true)
+			.required_unless("dynafed-params"),
+		opt("dynafed-params", "dynafed params in JSON, as in a decoded block, to take the fedpegscript from")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with("fedpeg-script"),
+	]
+}
+
+/// The options to select the fedpegscript, claim script and mainchain network of a peg-in.
+pub fn opts_pegin<'a>() -> Vec<clap::Arg<'a, 'a>> {
+	let mut opts = opts_fedpeg();
+	opts.extend(vec![
+		opt("claim-script", "the claim script in hex")
+			.takes_value(true)
+			.required_unless("claim-pubkey"),
+		opt("claim-pubkey", "a public key in hex to claim the peg-in to with a p2wpkh claim script")
+			.takes_value(true)
+			.required(false)
+			.conflicts_with("claim-script"),
+		opt_mainchain_network(),
+	]);
+	opts
+}
+
+/// The option to select the Bitcoin mainchain network.
+pub fn opt_mainchain_network<'a>() -> clap::Arg<'a, 'a> {
+	opt("mainchain-network", "the Bitcoin mainchain network (bitcoin, testnet, signet or regtest)")
+		.takes_value(true)
+		.required(false)
+}
+
+/// The fedpegscript from either the fedpegscript or the dynafed params option.
+pub fn fedpeg_script<'a>(matches: &clap::ArgMatches<'a>) -> bitcoin::Script {
+	if let Some(json) = matches.value_of("dynafed-params") {
+		let params: ParamsInfo = serde_json::from_str(json).expect("invalid dynafed params JSON");
+		params.fedpeg_script.expect("only full dynafed params have a fedpegscript").0.into()
+	} else {
+		hex::decode(matches.value_of("fedpeg-script").unwrap()).expect("invalid fedpegscript hex").into()
+	}
+}
+
+/// The claim script from either the claim script or the claim pubkey option.
+pub fn claim_script<'a>(matches: &clap::ArgMatches<'a>) -> Vec<u8> {
+	if let Some(pk) = matches.value_of("claim-pubkey") {
+		let pk: bitcoin::PublicKey = pk.parse().expect("invalid claim pubkey");
+		let hash = pk.wpubkey_hash().expect("claim pubkey must be compressed");
+		bitcoin::Script::new_v0_p2wpkh(&hash).into_bytes()
+	} else {
+		hex::decode(matches.value_of("claim-script").unwrap()).expect("invalid claim script hex")
+	}
+}
+
+/// The Bitcoin mainchain network, defaulting to that of the Elements network.
+pub fn mainchain_network<'a>(matches: &clap::ArgMatches<'a>, network: Network) -> bitcoin::Network {
+	match matches.value_of("mainchain-network") {
+		Some(n) => n.parse().expect("invalid mainchain network"),
+		None => network.mainchain_network().expect("--mainchain-network is required for custom chains"),
+	}
+}
+
+/// Parse an asset commitment in hex or an asset ID, which is taken as explicit asset.
+pub fn parse_asset(s: &str) -> Asset {
+	if s.len() == 64 {
+		Asset::Explicit(s.parse().expect("invalid asset ID"))
+	} else {
+		let bytes = hex::decode(s).expect("invalid asset commitment hex");
+		Asset::Confidential(Generator::from_slice(&bytes).expect("invalid asset commitment"))
 	}
 }
 
+/// Parse an asset commitment in hex or an asset ID, which is taken as unblinded asset.
+pub fn parse_asset_commitment(s: &str) -> Generator {
+	let secp = elements::secp256k1_zkp::Secp256k1::signing_only();
+	parse_asset(s).into_asset_gen(&secp).expect("asset is not null")
+}
+
+/// Parse a SLIP-77 master blinding key in hex.
+pub fn parse_slip77_key(s: &str) -> MasterBlindingKey {
+	let bytes = hex::decode(s).expect("invalid SLIP-77 master blinding key hex");
+	MasterBlindingKey(SecretKey::from_slice(&bytes).expect("invalid SLIP-77 master blinding key"))
+}
+
 pub fn opt_yaml<'a>() -> clap::Arg<'a, 'a> {
 	clap::Arg::with_name("yaml")
 		.long("yaml")
@@ -6,33 +6,47 @@ use bitcoin::hashes::Hash;
 use bitcoin;
 use elements::encode::{deserialize, serialize};
 use elements::{
-	confidential, AssetIssuance, OutPoint, Transaction, TxIn, TxInWitness, TxOut, TxOutWitness,
-	Script,
+	confidential, AssetIssuance, OutPoint, Transaction, TxIn, TxInWitness, TxOut, TxOutSecrets,
+	TxOutWitness, Script,
 };
 use elements::secp256k1_zkp::{
 	Generator, PedersenCommitment, PublicKey, RangeProof, SurjectionProof, Tweak,
 };
 
 use cmd;
-use hal_elements::Network;
+use hal_elements::{Chain, Network};
+use hal_elements::block::ParamsInfo;
 use hal_elements::confidential::{
 	ConfidentialAssetInfo, ConfidentialNonceInfo, ConfidentialType, ConfidentialValueInfo,
 };
+use hal_elements::pak::{
+	pak_list_from_extension_space, parse_pak_entry, verify_pak_proof, PakVerificationInfo,
+};
 use hal_elements::tx::{
 	AssetIssuanceInfo, InputInfo, InputWitnessInfo, OutputInfo, OutputWitnessInfo, PeginDataInfo,
-	PegoutDataInfo, TransactionInfo, InputScriptInfo, OutputScriptInfo,
+	PegoutDataInfo, TransactionInfo, InputScriptInfo, OutputScriptInfo, UnblindingKeys,
 };
 
 pub fn subcommand<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand_group("tx", "manipulate transactions")
 		.subcommand(cmd_create())
 		.subcommand(cmd_decode())
+		.subcommand(cmd_unblind())
+		.subcommand(cmd_verify())
+		.subcommand(cmd_verify_pegin())
+		.subcommand(cmd_pegin_input())
+		.subcommand(cmd_verify_pegout())
 }
 
 pub fn execute<'a>(matches: &clap::ArgMatches<'a>) {
 	match matches.subcommand() {
 		("create", Some(ref m)) => exec_create(&m),
 		("decode", Some(ref m)) => exec_decode(&m),
+		("unblind", Some(ref m)) => exec_unblind(&m),
+		("verify", Some(ref m)) => exec_verify(&m),
+		("verify-pegin", Some(ref m)) => exec_verify_pegin(&m),
+		("pegin-input", Some(ref m)) => exec_pegin_input(&m),
+		("verify-pegout", Some(ref m)) => exec_verify_pegout(&m),
 		(_, _) => unreachable!("clap prints help"),
 	};
 }
@@ -187,9 +201,10 @@ fn create_pegin_witness(pd: PeginDataInfo, prevout: bitcoin::OutPoint) -> Vec<Ve
 		serialize(&pd.value),
 		serialize(&asset),
 		serialize(&pd.genesis_hash),
-		serialize(&pd.claim_script.0),
-		serialize(&pd.mainchain_tx_hex.0),
-		serialize(&pd.merkle_proof.0),
+		// These are pushed as-is, without length prefix.
+		pd.claim_script.0,
+		pd.mainchain_tx_hex.0,
+		pd.merkle_proof.0,
 	]
 }
 
@@ -281,10 +296,9 @@ fn create_script_pubkey(spk: OutputScriptInfo, used_network: &mut Option<Network
 		panic!("Decoding script assembly is not yet supported.");
 	} else if let Some(address) = spk.address {
 		// Error if another network had already been used.
-		if let Some(network) = Network::from_params(address.params) {
-			if used_network.replace(network).unwrap_or(network) != network {
-				panic!("Addresses for different networks are used in the output scripts.");
-			}
+		let network = Network::from_params(address.params);
+		if used_network.replace(network).unwrap_or(network) != network {
+			panic!("Addresses for different networks are used in the output scripts.");
 		}
 
 		address.script_pubkey()
@@ -361,7 +375,18 @@ fn create_output(output: OutputInfo) -> TxOut {
 	TxOut {
 		asset: asset,
 		value: value,
-		nonce: output.nonce.map(create_confidential_nonce).unwrap_or(confidential::Nonce::Null),
+		nonce: if let Some(blinder) = output.blinding_pubkey {
+			if output.nonce.is_some() {
+				warn!("Field \"nonce\" of output is ignored.");
+			}
+			if output.witness.is_some() {
+				warn!("Field \"witness\" of output is ignored.");
+			}
+			// Outputs are blinded to the pubkey in the nonce field.
+			confidential::Nonce::Confidential(blinder)
+		} else {
+			output.nonce.map(create_confidential_nonce).unwrap_or(confidential::Nonce::Null)
+		},
 		script_pubkey: if let Some(spk) = output.script_pub_key {
 			if output.pegout_data.is_some() {
 				warn!("Field \"pegout_data\" of output is ignored.");
@@ -383,7 +408,11 @@ fn create_output(output: OutputInfo) -> TxOut {
 		} else {
 			Default::default()
 		},
-		witness: output.witness.map(create_output_witness).unwrap_or_default(),
+		witness: if output.blinding_pubkey.is_some() {
+			Default::default()
+		} else {
+			output.witness.map(create_output_witness).unwrap_or_default()
+		},
 	}
 }
 
@@ -405,22 +434,37 @@ pub fn create_transaction(info: TransactionInfo) -> Transaction {
 		warn!("Field \"vsize\" is ignored.");
 	}
 
-	Transaction {
+	let inputs = info.inputs.expect("Field \"inputs\" is required.");
+	let outputs = info.outputs.expect("Field \"outputs\" is required.");
+
+	let blind = outputs.iter().any(|o| o.blinding_pubkey.is_some());
+	let spent_secrets = if blind {
+		inputs.iter().map(|i| {
+			let secrets = i.prevout_secrets.as_ref()
+				.expect("Field \"prevout_secrets\" is required for all inputs to blind outputs.");
+			TxOutSecrets::new(
+				secrets.asset,
+				secrets.asset_blinding_factor,
+				secrets.value,
+				secrets.value_blinding_factor,
+			)
+		}).collect()
+	} else {
+		Vec::new()
+	};
+
+	let mut tx = Transaction {
 		version: info.version.expect("Field \"version\" is required."),
 		lock_time: elements::PackedLockTime(info.locktime.expect("Field \"locktime\" is required.")),
-		input: info
-			.inputs
-			.expect("Field \"inputs\" is required.")
-			.into_iter()
-			.map(create_input)
-			.collect(),
-		output: info
-			.outputs
-			.expect("Field \"outputs\" is required.")
-			.into_iter()
-			.map(create_output)
-			.collect(),
+		input: inputs.into_iter().map(create_input).collect(),
+		output: outputs.into_iter().map(create_output).collect(),
+	};
+
+	if blind {
+		hal_elements::tx::blind(&mut tx, &spent_secrets)
+			.unwrap_or_else(|e| panic!("failed to blind transaction: {}", e));
 	}
+	tx
 }
 
 fn exec_create<'a>(matches: &clap::ArgMatches<'a>) {
@@ -439,14 +483,302 @@ fn exec_create<'a>(matches: &clap::ArgMatches<'a>) {
 fn cmd_decode<'a>() -> clap::App<'a, 'a> {
 	cmd::subcommand("decode", "decode a raw transaction to JSON")
 		.args(&cmd::opts_networks())
-		.args(&[cmd::opt_yaml(), cmd::arg("raw-tx", "the raw transaction in hex").required(false)])
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::opt_asset_registry(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt(
+				"input-asset",
+				"the asset ID or asset commitment of the output spent by each input, in order, \
+				to verify the surjection proofs (must be used once for every input)",
+			)
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required(false),
+		])
 }
 
 fn exec_decode<'a>(matches: &clap::ArgMatches<'a>) {
+	let registry = cmd::asset_registry(matches);
+	let chain = Chain {
+		registry: registry.as_ref(),
+		..cmd::chain(matches)
+	};
+	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
+	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
+	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
+
+	let mut info: TransactionInfo = ::GetInfo::get_info(&tx, chain);
+	if let Some(assets) = matches.values_of("input-asset") {
+		let spent_assets = assets.map(cmd::parse_asset).collect::<Vec<_>>();
+		let domain = hal_elements::tx::surjection_domain(&tx, &spent_assets)
+			.unwrap_or_else(|e| panic!("invalid input assets: {}", e));
+		info.verify_surjection_proofs(&tx, &domain);
+	}
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_unblind<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("unblind", "decode a raw transaction to JSON and unblind its confidential outputs")
+		.args(&cmd::opts_networks())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::opt_asset_registry(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt("blinding-key", "a blinding private key in hex (can be used multiple times)")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required_unless_one(&["slip77-key", "issuance-blinding-key"]),
+			cmd::opt("slip77-key", "a SLIP-77 master blinding key in hex to derive blinding keys")
+				.takes_value(true)
+				.required(false),
+			cmd::opt(
+				"issuance-blinding-key",
+				"an issuance blinding private key in hex (can be used multiple times)",
+			)
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required(false),
+		])
+}
+
+fn exec_unblind<'a>(matches: &clap::ArgMatches<'a>) {
+	let registry = cmd::asset_registry(matches);
+	let chain = Chain {
+		registry: registry.as_ref(),
+		..cmd::chain(matches)
+	};
+	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
+	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
+	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
+
+	let keys = UnblindingKeys {
+		blinding_keys: matches.values_of("blinding-key").map(|keys| keys.map(|k| {
+			k.parse().unwrap_or_else(|_| panic!("invalid blinding key: {}", k))
+		}).collect::<Vec<_>>()).unwrap_or_default(),
+		slip77: matches.value_of("slip77-key").map(cmd::parse_slip77_key),
+		issuance_blinding_keys: matches.values_of("issuance-blinding-key").map(|keys| keys.map(|k| {
+			k.parse().unwrap_or_else(|_| panic!("invalid issuance blinding key: {}", k))
+		}).collect::<Vec<_>>()).unwrap_or_default(),
+	};
+
+	let info = hal_elements::tx::unblinded_info(&tx, &keys, chain);
+	let outputs = info.outputs.as_ref().expect("outputs are set");
+	if (!keys.blinding_keys.is_empty() || keys.slip77.is_some())
+		&& outputs.iter().all(|o| o.unblinded.is_none())
+	{
+		warn!("None of the outputs could be unblinded with the given keys.");
+	}
+	let inputs = info.inputs.as_ref().expect("inputs are set");
+	if !keys.issuance_blinding_keys.is_empty() && inputs.iter().all(|i| {
+		i.asset_issuance.as_ref().map(|a| a.unblinded_amount.is_none() && a.unblinded_inflation_keys.is_none())
+			.unwrap_or(true)
+	}) {
+		warn!("None of the issuances could be unblinded with the given issuance keys.");
+	}
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_verify<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("verify", "verify the value balance and all proofs of a raw transaction")
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt("funding-tx", "a raw transaction in hex with outputs spent by the transaction")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required(false),
+			cmd::opt(
+				"prevout",
+				"a spent output in JSON, for every input without funding tx, in order \
+				(peg-in inputs take their spent output from the peg-in witness)",
+			)
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required(false),
+		])
+}
+
+fn exec_verify<'a>(matches: &clap::ArgMatches<'a>) {
 	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
 	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
 	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
 
-	let info = ::GetInfo::get_info(&tx, cmd::network(matches));
+	let funding_txs = matches.values_of("funding-tx").map(|txs| txs.map(|hex_tx| {
+		let raw_tx = hex::decode(hex_tx).expect("could not decode raw funding tx");
+		deserialize::<Transaction>(&raw_tx).expect("invalid funding tx format")
+	}).collect::<Vec<_>>()).unwrap_or_default();
+	let mut prevouts = matches.values_of("prevout").map(|outs| outs.map(|json| {
+		let info: OutputInfo = serde_json::from_str(json).expect("invalid prevout JSON");
+		create_output(info)
+	}).collect::<Vec<_>>()).unwrap_or_default().into_iter();
+
+	let spent_outputs = tx.input.iter().enumerate().map(|(i, input)| {
+		if let Some(pegin) = input.pegin_data() {
+			return TxOut {
+				asset: confidential::Asset::Explicit(pegin.asset),
+				value: confidential::Value::Explicit(pegin.value),
+				..Default::default()
+			};
+		}
+		let prevout = input.previous_output;
+		let funding = funding_txs.iter().find(|tx| tx.txid() == prevout.txid);
+		match funding {
+			Some(tx) => tx.output.get(prevout.vout as usize).cloned()
+				.unwrap_or_else(|| panic!("funding tx of input {} has no output {}", i, prevout.vout)),
+			None => prevouts.next().unwrap_or_else(|| panic!("no spent output given for input {}", i)),
+		}
+	}).collect::<Vec<_>>();
+	if prevouts.next().is_some() {
+		panic!("more prevouts given than inputs without funding tx");
+	}
+
+	let info = hal_elements::tx::verify(&tx, &spent_outputs).unwrap_or_else(|e| panic!("{}", e));
 	cmd::print_output(matches, &info)
 }
+
+fn cmd_verify_pegin<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("verify-pegin", "verify the mainchain tx and merkle proof of the peg-in inputs of a raw transaction")
+		.args(&cmd::opts_fedpeg())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt("block-header", "a raw mainchain block header in hex referenced by a peg-in")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required(false),
+		])
+}
+
+fn exec_verify_pegin<'a>(matches: &clap::ArgMatches<'a>) {
+	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
+	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
+	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
+
+	let headers = matches.values_of("block-header").map(|hs| hs.map(|h| {
+		let raw = hex::decode(h).expect("could not decode block header hex");
+		bitcoin::consensus::encode::deserialize::<bitcoin::BlockHeader>(&raw).expect("invalid block header")
+	}).collect::<Vec<_>>()).unwrap_or_default();
+	let fedpeg_script = cmd::fedpeg_script(matches);
+
+	let infos = tx.input.iter().enumerate().filter_map(|(i, input)| {
+		let pegin = input.pegin_data()?;
+		let header = headers.iter().find(|h| h.block_hash() == pegin.referenced_block);
+		if !headers.is_empty() && header.is_none() {
+			warn!("No block header given for the block referenced by input {}.", i);
+		}
+		Some(hal_elements::pegin::verify_pegin(i, &pegin, header, Some(&fedpeg_script)))
+	}).collect::<Vec<_>>();
+	if infos.is_empty() {
+		panic!("transaction has no peg-in inputs");
+	}
+	cmd::print_output(matches, &infos)
+}
+
+fn cmd_pegin_input<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("pegin-input", "create a peg-in input in JSON for tx create from a mainchain tx and merkle proof")
+		.args(&cmd::opts_networks())
+		.args(&cmd::opts_pegin())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::opt("mainchain-tx", "the raw mainchain transaction in hex")
+				.takes_value(true)
+				.required(true),
+			cmd::opt("merkle-proof", "the merkle proof of the mainchain tx in hex, as given by gettxoutproof")
+				.takes_value(true)
+				.required(true),
+			cmd::opt("asset", "the asset ID to peg in (default: the policy asset of the network)")
+				.takes_value(true)
+				.required(false),
+		])
+}
+
+fn exec_pegin_input<'a>(matches: &clap::ArgMatches<'a>) {
+	let chain = cmd::chain(matches);
+	let asset = match matches.value_of("asset") {
+		Some(a) => a.parse().expect("invalid asset ID"),
+		None => chain.policy_asset.expect("--asset is required when the network has no policy asset"),
+	};
+	let mainchain_network = cmd::mainchain_network(matches, chain.network);
+	let genesis_hash = bitcoin::blockdata::constants::genesis_block(mainchain_network).block_hash();
+
+	let mainchain_tx = hex::decode(matches.value_of("mainchain-tx").unwrap()).expect("invalid mainchain tx hex");
+	let merkle_proof = hex::decode(matches.value_of("merkle-proof").unwrap()).expect("invalid merkle proof hex");
+	let txin = hal_elements::pegin::pegin_input(
+		&mainchain_tx,
+		&merkle_proof,
+		&cmd::fedpeg_script(matches),
+		&cmd::claim_script(matches),
+		asset,
+		genesis_hash,
+	).unwrap_or_else(|e| panic!("{}", e));
+
+	// Only keep what tx create needs, the peg-in witness is created from the peg-in data.
+	let mut info: InputInfo = ::GetInfo::get_info(&txin, chain);
+	info.script_sig = None;
+	info.sequence = None;
+	info.has_issuance = None;
+	info.witness = None;
+	cmd::print_output(matches, &info)
+}
+
+fn cmd_verify_pegout<'a>() -> clap::App<'a, 'a> {
+	cmd::subcommand("verify-pegout", "verify the PAK proofs of the peg-out outputs of a raw transaction")
+		.args(&cmd::opts_networks())
+		.args(&[
+			cmd::opt_yaml(),
+			cmd::opt_mainchain_network(),
+			cmd::arg("raw-tx", "the raw transaction in hex").required(false),
+			cmd::opt("pak", "a PAK list entry, like <offline key>:<online key>")
+				.takes_value(true)
+				.multiple(true)
+				.number_of_values(1)
+				.required_unless("dynafed-params"),
+			cmd::opt("dynafed-params", "dynafed params in JSON, as in a decoded block, to take the PAK list from")
+				.takes_value(true)
+				.required(false)
+				.conflicts_with("pak"),
+		])
+}
+
+fn exec_verify_pegout<'a>(matches: &clap::ArgMatches<'a>) {
+	let hex_tx = cmd::arg_or_stdin(matches, "raw-tx");
+	let raw_tx = hex::decode(hex_tx.as_ref()).expect("could not decode raw tx");
+	let tx: Transaction = deserialize(&raw_tx).expect("invalid tx format");
+
+	let pak_list = if let Some(json) = matches.value_of("dynafed-params") {
+		let params: ParamsInfo = serde_json::from_str(json).expect("invalid dynafed params JSON");
+		let extension_space = params.extension_space.expect("only full dynafed params have a PAK list")
+			.into_iter().map(|e| e.0).collect::<Vec<_>>();
+		pak_list_from_extension_space(&extension_space).unwrap_or_else(|e| panic!("invalid PAK list: {}", e))
+	} else {
+		matches.values_of("pak").unwrap().map(|p| {
+			parse_pak_entry(p).unwrap_or_else(|e| panic!("invalid PAK entry: {}", e))
+		}).collect()
+	};
+	if pak_list.is_empty() {
+		warn!("PAK list is empty, peg-outs are not restricted.");
+	}
+	let mainchain_network = cmd::mainchain_network(matches, cmd::network(matches));
+	let genesis_hash = bitcoin::blockdata::constants::genesis_block(mainchain_network).block_hash();
+
+	let infos = tx.output.iter().enumerate().filter_map(|(i, output)| {
+		let pegout = output.pegout_data()?;
+		let result = verify_pak_proof(&pegout, &pak_list, genesis_hash);
+		Some(PakVerificationInfo {
+			index: i,
+			valid: result.is_ok(),
+			error: result.err(),
+		})
+	}).collect::<Vec<_>>();
+	if infos.is_empty() {
+		panic!("transaction has no peg-out outputs");
+	}
+	cmd::print_output(matches, &infos)
+}
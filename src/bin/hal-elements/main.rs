@@ -4,6 +4,7 @@ extern crate clap;
 extern crate elements;
 extern crate fern;
 extern crate hex;
+extern crate serde;
 extern crate serde_json;
 
 extern crate hal;
@@ -14,7 +15,7 @@ use std::process;
 
 pub use elements::bitcoin;
 
-pub use hal_elements::{GetInfo, Network};
+pub use hal_elements::{Chain, GetInfo, Network};
 
 pub mod cmd;
 
@@ -56,7 +57,11 @@ fn init_app<'a, 'b>() -> clap::App<'a, 'b> {
 fn execute_builtin<'a>(matches: &clap::ArgMatches<'a>) -> bool {
 	match matches.subcommand() {
 		("address", Some(ref m)) => cmd::address::execute(&m),
+		("asset", Some(ref m)) => cmd::asset::execute(&m),
+		("bip32", Some(ref m)) => cmd::bip32::execute(&m),
 		("block", Some(ref m)) => cmd::block::execute(&m),
+		("confidential", Some(ref m)) => cmd::confidential::execute(&m),
+		("descriptor", Some(ref m)) => cmd::descriptor::execute(&m),
 		("tx", Some(ref m)) => cmd::tx::execute(&m),
 		_ => return false,
 	};
@@ -1,21 +1,26 @@
-use bitcoin::{Address, Network};
+use bitcoin::{secp256k1, XpubIdentifier};
+use bitcoin::util::bip32;
+use serde::{Deserialize, Serialize};
+
+use address::Addresses;
+use Network;
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct DerivationInfo {
 	pub network: Network,
-	pub master_fingerprint: ::HexBytes,
-	pub path: String,
-	pub chain_code: ::HexBytes,
-	pub identifier: ::HexBytes,
-	pub fingerprint: ::HexBytes,
-	pub public_key: ::HexBytes,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub secret_key: Option<String>,
-	pub parent_fingerprint: ::HexBytes,
-	#[serde(rename = "address-p2pkh")]
-	pub address_p2pkh: Address,
-	#[serde(rename = "address-p2wpkh")]
-	pub address_p2wpkh: Address,
-	#[serde(rename = "address-p2shwpkh")]
-	pub address_p2shwpkh: Address,
+	pub master_fingerprint: Option<bip32::Fingerprint>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub path: Option<bip32::DerivationPath>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub xpriv: Option<bip32::ExtendedPrivKey>,
+	pub xpub: bip32::ExtendedPubKey,
+	pub chain_code: bip32::ChainCode,
+	pub identifier: XpubIdentifier,
+	pub fingerprint: bip32::Fingerprint,
+	pub public_key: secp256k1::PublicKey,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub private_key: Option<secp256k1::SecretKey>,
+	pub parent_fingerprint: bip32::Fingerprint,
+	pub addresses: Addresses,
 }
@@ -2,8 +2,9 @@ use bitcoin::hashes::sha256;
 use elements::{dynafed, Block, BlockExtData, BlockHeader, BlockHash, TxMerkleNode, Txid};
 use serde::{Deserialize, Serialize};
 
-use ::{GetInfo, Network, HexBytes};
+use ::{Chain, GetInfo, HexBytes};
 
+use pak::{pak_list_from_extension_space, PakEntryInfo};
 use tx::TransactionInfo;
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
@@ -36,10 +37,13 @@ pub struct ParamsInfo {
 	pub fedpeg_script: Option<HexBytes>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extension_space: Option<Vec<HexBytes>>,
+	/// The PAK list in the extension space, if it holds a valid one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pak_list: Option<Vec<PakEntryInfo>>,
 }
 
 impl<'a> GetInfo<ParamsInfo> for dynafed::Params {
-	fn get_info(&self, _network: Network) -> ParamsInfo {
+	fn get_info(&self, _chain: Chain) -> ParamsInfo {
 		ParamsInfo {
 			params_type: match self {
 				dynafed::Params::Null => ParamsType::Null,
@@ -58,6 +62,10 @@ impl<'a> GetInfo<ParamsInfo> for dynafed::Params {
 			extension_space: self
 				.extension_space()
 				.map(|s| s.iter().map(|v| v[..].into()).collect()),
+			pak_list: self
+				.extension_space()
+				.filter(|s| !s.is_empty())
+				.and_then(|s| pak_list_from_extension_space(s).ok()),
 		}
 	}
 }
@@ -85,7 +93,7 @@ pub struct BlockHeaderInfo {
 }
 
 impl<'a> GetInfo<BlockHeaderInfo> for BlockHeader {
-	fn get_info(&self, network: Network) -> BlockHeaderInfo {
+	fn get_info(&self, chain: Chain) -> BlockHeaderInfo {
 		let mut info = BlockHeaderInfo {
 			block_hash: Some(self.block_hash()),
 			version: self.version,
@@ -115,8 +123,8 @@ impl<'a> GetInfo<BlockHeaderInfo> for BlockHeader {
 				ref signblock_witness,
 			} => {
 				info.dynafed = true;
-				info.dynafed_current = Some(current.get_info(network));
-				info.dynafed_proposed = Some(proposed.get_info(network));
+				info.dynafed_current = Some(current.get_info(chain));
+				info.dynafed_proposed = Some(proposed.get_info(chain));
 				info.dynafed_witness =
 					Some(signblock_witness.iter().map(|b| b[..].into()).collect());
 			}
@@ -138,10 +146,10 @@ pub struct BlockInfo {
 }
 
 impl GetInfo<BlockInfo> for Block {
-	fn get_info(&self, network: Network) -> BlockInfo {
+	fn get_info(&self, chain: Chain) -> BlockInfo {
 		BlockInfo {
-			header: self.header.get_info(network),
-			transactions: Some(self.txdata.iter().map(|t| t.get_info(network)).collect()),
+			header: self.header.get_info(chain),
+			transactions: Some(self.txdata.iter().map(|t| t.get_info(chain)).collect()),
 			txids: None,
 			raw_transactions: None,
 		}
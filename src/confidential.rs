@@ -1,8 +1,10 @@
-use elements::confidential::{Asset, Nonce, Value};
-use elements::AssetId;
+use elements::confidential::{Asset, AssetBlindingFactor, Nonce, Value, ValueBlindingFactor};
+use elements::secp256k1_zkp::{Generator, PedersenCommitment, Secp256k1};
+use elements::{AssetId, TxOutSecrets};
 use serde::{Deserialize, Serialize};
 
-use ::{GetInfo, Network, HexBytes};
+use ::{Chain, GetInfo, HexBytes};
+use registry::{asset_metadata, AssetMetadata};
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -20,10 +22,13 @@ pub struct ConfidentialValueInfo {
 	pub value: Option<u64>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub commitment: Option<HexBytes>,
+	/// The value in the decimal unit of the asset, if its precision is known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub decimal_value: Option<String>,
 }
 
 impl GetInfo<ConfidentialValueInfo> for Value {
-	fn get_info(&self, _network: Network) -> ConfidentialValueInfo {
+	fn get_info(&self, _chain: Chain) -> ConfidentialValueInfo {
 		ConfidentialValueInfo {
 			type_: match self {
 				Value::Null => ConfidentialType::Null,
@@ -38,6 +43,7 @@ impl GetInfo<ConfidentialValueInfo> for Value {
 				Value::Confidential(pk) => Some(pk.serialize()[..].into()),
 				_ => None,
 			},
+			decimal_value: None,
 		}
 	}
 }
@@ -46,16 +52,22 @@ impl GetInfo<ConfidentialValueInfo> for Value {
 #[serde(rename_all = "snake_case")]
 pub enum ConfidentialAssetLabel {
 	LiquidBitcoin,
+	LiquidTestnetBitcoin,
+	/// The policy asset of a regtest or custom chain.
+	PolicyAsset,
 }
 
 impl ConfidentialAssetLabel {
-	pub fn from_asset_id(id: AssetId) -> Option<ConfidentialAssetLabel> {
-		match id.to_string().as_str() {
-			"6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d" => {
-				Some(ConfidentialAssetLabel::LiquidBitcoin)
-			}
-			_ => None,
+	/// The label of the asset if it's the policy asset of the given chain.
+	pub fn from_asset_id(id: AssetId, chain: Chain) -> Option<ConfidentialAssetLabel> {
+		if chain.policy_asset != Some(id) {
+			return None;
 		}
+		Some(match id.to_string().as_str() {
+			::LIQUID_POLICY_ASSET => ConfidentialAssetLabel::LiquidBitcoin,
+			::LIQUID_TESTNET_POLICY_ASSET => ConfidentialAssetLabel::LiquidTestnetBitcoin,
+			_ => ConfidentialAssetLabel::PolicyAsset,
+		})
 	}
 }
 
@@ -69,10 +81,12 @@ pub struct ConfidentialAssetInfo {
 	pub commitment: Option<HexBytes>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub label: Option<ConfidentialAssetLabel>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub metadata: Option<AssetMetadata>,
 }
 
 impl GetInfo<ConfidentialAssetInfo> for Asset {
-	fn get_info(&self, _network: Network) -> ConfidentialAssetInfo {
+	fn get_info(&self, chain: Chain) -> ConfidentialAssetInfo {
 		ConfidentialAssetInfo {
 			type_: match self {
 				Asset::Null => ConfidentialType::Null,
@@ -88,7 +102,11 @@ impl GetInfo<ConfidentialAssetInfo> for Asset {
 				_ => None,
 			},
 			label: match self {
-				Asset::Explicit(a) => ConfidentialAssetLabel::from_asset_id(*a),
+				Asset::Explicit(a) => ConfidentialAssetLabel::from_asset_id(*a, chain),
+				_ => None,
+			},
+			metadata: match self {
+				Asset::Explicit(a) => asset_metadata(*a, chain),
 				_ => None,
 			},
 		}
@@ -96,16 +114,50 @@ impl GetInfo<ConfidentialAssetInfo> for Asset {
 }
 
 impl GetInfo<ConfidentialAssetInfo> for AssetId {
-	fn get_info(&self, _network: Network) -> ConfidentialAssetInfo {
+	fn get_info(&self, chain: Chain) -> ConfidentialAssetInfo {
 		ConfidentialAssetInfo {
 			type_: ConfidentialType::Explicit,
 			asset: Some(*self),
 			commitment: None,
-			label: ConfidentialAssetLabel::from_asset_id(*self),
+			label: ConfidentialAssetLabel::from_asset_id(*self, chain),
+			metadata: asset_metadata(*self, chain),
 		}
 	}
 }
 
+/// The asset commitment of the asset blinded with the asset blinding factor.
+pub fn asset_commitment(asset: AssetId, asset_blinding_factor: AssetBlindingFactor) -> Generator {
+	let secp = Secp256k1::signing_only();
+	Generator::new_blinded(&secp, asset.into_tag(), asset_blinding_factor.into_inner())
+}
+
+/// The value commitment of the value of the given asset commitment,
+/// blinded with the value blinding factor.
+pub fn value_commitment(
+	value: u64,
+	asset_commitment: Generator,
+	value_blinding_factor: ValueBlindingFactor,
+) -> PedersenCommitment {
+	let secp = Secp256k1::signing_only();
+	PedersenCommitment::new(&secp, value, value_blinding_factor.into_inner(), asset_commitment)
+}
+
+/// The result of opening commitments with claimed secrets.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct CommitmentOpeningInfo {
+	/// The asset commitment computed from the claimed secrets.
+	pub asset_commitment: HexBytes,
+	/// Whether the given asset commitment matches the claimed secrets.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub asset_commitment_valid: Option<bool>,
+	/// The value commitment computed from the claimed secrets.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value_commitment: Option<HexBytes>,
+	/// Whether the given value commitment matches the claimed secrets.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value_commitment_valid: Option<bool>,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct ConfidentialNonceInfo {
 	#[serde(rename = "type")]
@@ -117,7 +169,7 @@ pub struct ConfidentialNonceInfo {
 }
 
 impl GetInfo<ConfidentialNonceInfo> for Nonce {
-	fn get_info(&self, _network: Network) -> ConfidentialNonceInfo {
+	fn get_info(&self, _chain: Chain) -> ConfidentialNonceInfo {
 		ConfidentialNonceInfo {
 			type_: match self {
 				Nonce::Null => ConfidentialType::Null,
@@ -135,3 +187,243 @@ impl GetInfo<ConfidentialNonceInfo> for Nonce {
 		}
 	}
 }
+
+/// The secrets of a confidential output, revealed by unblinding it.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct UnblindedOutputInfo {
+	pub asset: AssetId,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub label: Option<ConfidentialAssetLabel>,
+	pub value: u64,
+	/// The value in the decimal unit of the asset, if its precision is known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub decimal_value: Option<String>,
+	pub asset_blinding_factor: AssetBlindingFactor,
+	pub value_blinding_factor: ValueBlindingFactor,
+}
+
+impl GetInfo<UnblindedOutputInfo> for TxOutSecrets {
+	fn get_info(&self, chain: Chain) -> UnblindedOutputInfo {
+		UnblindedOutputInfo {
+			asset: self.asset,
+			label: ConfidentialAssetLabel::from_asset_id(self.asset, chain),
+			value: self.value,
+			decimal_value: asset_metadata(self.asset, chain).map(|m| m.format_value(self.value)),
+			asset_blinding_factor: self.asset_bf,
+			value_blinding_factor: self.value_bf,
+		}
+	}
+}
+
+/// Information decoded from the header of a rangeproof.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct RangeProofInfo {
+	/// The size of the proof in bytes.
+	pub size: usize,
+	/// The base-10 exponent the value is scaled with, -1 for proofs of an exact value.
+	pub exponent: i32,
+	/// The number of bits of the proven mantissa, 0 for proofs of an exact value.
+	pub mantissa_bits: u32,
+	pub min_value: u64,
+	pub max_value: u64,
+}
+
+impl RangeProofInfo {
+	/// Decode the rangeproof header like libsecp256k1-zkp's rangeproof_info does.
+	///
+	/// Returns [None] if the header is malformed.
+	pub fn from_proof(proof: &[u8]) -> Option<RangeProofInfo> {
+		// The header is followed by at least one ring, so shorter proofs are invalid.
+		if proof.len() < 65 {
+			return None;
+		}
+		let flags = proof[0];
+		// The highest bit is reserved.
+		if flags & 0x80 != 0 {
+			return None;
+		}
+
+		let mut offset = 1;
+		let (exponent, mantissa_bits, mut max_value) = if flags & 0x40 != 0 {
+			let exponent = (flags & 0x1f) as u32;
+			let mantissa_bits = *proof.get(offset)? as u32 + 1;
+			offset += 1;
+			if exponent > 18 || mantissa_bits > 64 {
+				return None;
+			}
+			let max_value = u64::MAX >> (64 - mantissa_bits);
+			(exponent as i32, mantissa_bits, max_value.checked_mul(10u64.pow(exponent))?)
+		} else {
+			(-1, 0, 0)
+		};
+
+		let min_value = if flags & 0x20 != 0 {
+			let mut bytes = [0u8; 8];
+			bytes.copy_from_slice(proof.get(offset..offset + 8)?);
+			u64::from_be_bytes(bytes)
+		} else {
+			0
+		};
+		max_value = max_value.checked_add(min_value)?;
+
+		Some(RangeProofInfo {
+			size: proof.len(),
+			exponent,
+			mantissa_bits,
+			min_value,
+			max_value,
+		})
+	}
+}
+
+/// Information decoded from a surjection proof.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct SurjectionProofInfo {
+	/// The size of the proof in bytes.
+	pub size: usize,
+	/// The number of inputs in the proof domain.
+	pub input_count: usize,
+	/// The bitmap of domain inputs used in the proof.
+	pub used_input_bitmap: HexBytes,
+	/// The indices of the domain inputs used in the proof.
+	pub used_inputs: Vec<usize>,
+	/// Whether the proof is valid, if the input asset generators are known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub verified: Option<bool>,
+}
+
+impl SurjectionProofInfo {
+	/// Decode the surjection proof header.
+	///
+	/// Returns [None] if the header is malformed.
+	// usize::div_ceil requires Rust 1.73.
+	#[allow(clippy::manual_div_ceil)]
+	pub fn from_proof(proof: &[u8]) -> Option<SurjectionProofInfo> {
+		// The input count is encoded as little-endian u16, followed by the bitmap.
+		let input_count = *proof.first()? as usize | (*proof.get(1)? as usize) << 8;
+		let bitmap = proof.get(2..2 + (input_count + 7) / 8)?;
+		Some(SurjectionProofInfo {
+			size: proof.len(),
+			input_count,
+			used_input_bitmap: bitmap.into(),
+			used_inputs: (0..input_count).filter(|i| bitmap[i / 8] & (1 << (i % 8)) != 0).collect(),
+			verified: None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use elements::secp256k1_zkp::{RangeProof, SecretKey, SurjectionProof, Tag, Tweak};
+	use bitcoin::hashes::hex::FromHex;
+
+	/// Create a rangeproof for the value and return it with its range as verified by libsecp.
+	fn rangeproof(value: u64, min_value: u64, exp: i32, min_bits: u8) -> (Vec<u8>, u64, u64) {
+		let secp = Secp256k1::new();
+		let generator = Generator::new_unblinded(&secp, Tag::from([1; 32]));
+		let blinding = Tweak::from_slice(&[2; 32]).unwrap();
+		let commitment = PedersenCommitment::new(&secp, value, blinding, generator);
+		let sk = SecretKey::from_slice(&[3; 32]).unwrap();
+		let proof = RangeProof::new(
+			&secp, min_value, commitment, value, blinding, &[], &[], sk, exp, min_bits, generator,
+		).unwrap();
+		let range = proof.verify(&secp, commitment, &[], generator).unwrap();
+		(proof.serialize(), range.start, range.end - 1)
+	}
+
+	#[test]
+	fn rangeproof_info() {
+		// The proofs Elements creates for output amounts.
+		let (proof, min, max) = rangeproof(100_000, 1, 0, 52);
+		let info = RangeProofInfo::from_proof(&proof).unwrap();
+		assert_eq!(info.size, proof.len());
+		assert_eq!((info.exponent, info.mantissa_bits), (0, 52));
+		assert_eq!((info.min_value, info.max_value), (min, max));
+		assert_eq!((min, max), (1, 1 << 52));
+
+		let (proof, min, max) = rangeproof(123_456, 0, 2, 20);
+		let info = RangeProofInfo::from_proof(&proof).unwrap();
+		assert_eq!(info.exponent, 2);
+		assert_eq!((info.min_value, info.max_value), (min, max));
+
+		// Proofs of an exact value.
+		let (proof, min, max) = rangeproof(123_456, 123_456, -1, 0);
+		let info = RangeProofInfo::from_proof(&proof).unwrap();
+		assert_eq!((info.exponent, info.mantissa_bits), (-1, 0));
+		assert_eq!((info.min_value, info.max_value), (min, max));
+		assert_eq!(max, 123_456);
+	}
+
+	#[test]
+	fn rangeproof_info_invalid() {
+		let (proof, _, _) = rangeproof(100_000, 1, 0, 52);
+		assert_eq!(RangeProofInfo::from_proof(&[]), None);
+		assert_eq!(RangeProofInfo::from_proof(&proof[..64]), None);
+		assert!(RangeProofInfo::from_proof(&proof[..65]).is_some());
+
+		let header = |bytes: &[u8]| {
+			let mut proof = bytes.to_vec();
+			proof.resize(65, 0);
+			RangeProofInfo::from_proof(&proof)
+		};
+		// The reserved flag bit.
+		assert_eq!(header(&[0x80]), None);
+		// Exponents above 18 and more than 64 mantissa bits.
+		assert_eq!(header(&[0x40 | 19, 0]), None);
+		assert_eq!(header(&[0x40, 64]), None);
+		assert!(header(&[0x40, 63]).is_some());
+		// The maximum value overflows.
+		assert_eq!(header(&[0x40 | 1, 63]), None);
+		assert_eq!(header(&[0x60, 63, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]), None);
+	}
+
+	/// A surjection proof of an output in the elements crate's confidential tx test.
+	const SURJECTION_PROOF: &str = "0100012bfbd82937b25fc506c0b016d904d63eb15301c8fabc6e796549f0383c\
+		c08c7effd3d398b04e4feb1279f90f7c32d9a87907155514d69f9612b059f6fdf2a62f";
+
+	#[test]
+	fn surjection_proof_info() {
+		let proof = Vec::<u8>::from_hex(SURJECTION_PROOF).unwrap();
+		let info = SurjectionProofInfo::from_proof(&proof).unwrap();
+		assert_eq!((info.size, info.input_count), (67, 1));
+		assert_eq!(info.used_input_bitmap.0, vec![0x01]);
+		assert_eq!(info.used_inputs, vec![0]);
+
+		// A proof for the sixth of ten inputs, which takes two bitmap bytes.
+		let secp = Secp256k1::new();
+		let blinding = |i: u8| Tweak::from_slice(&[i + 1; 32]).unwrap();
+		let domain = (0..10u8).map(|i| {
+			let tag = Tag::from([i; 32]);
+			(Generator::new_blinded(&secp, tag, blinding(i)), tag, blinding(i))
+		}).collect::<Vec<_>>();
+		let codomain_blinding = Tweak::from_slice(&[42; 32]).unwrap();
+		let proof = SurjectionProof::new(
+			&secp, &mut ::rand::thread_rng(), Tag::from([5; 32]), codomain_blinding, &domain,
+		).unwrap();
+		let codomain = Generator::new_blinded(&secp, Tag::from([5; 32]), codomain_blinding);
+		let generators = domain.iter().map(|d| d.0).collect::<Vec<_>>();
+		assert!(proof.verify(&secp, codomain, &generators));
+
+		let proof = proof.serialize();
+		let info = SurjectionProofInfo::from_proof(&proof).unwrap();
+		assert_eq!((info.size, info.input_count), (proof.len(), 10));
+		assert_eq!(info.used_input_bitmap.0.len(), 2);
+		assert!(info.used_inputs.contains(&5));
+		assert!(info.used_inputs.iter().all(|&i| i < 10));
+		// Every used input has a 32-byte proof element, next to the 32-byte challenge.
+		assert_eq!(proof.len(), 2 + 2 + 32 * (info.used_inputs.len() + 1));
+	}
+
+	#[test]
+	fn surjection_proof_info_truncated() {
+		assert_eq!(SurjectionProofInfo::from_proof(&[]), None);
+		assert_eq!(SurjectionProofInfo::from_proof(&[0x01]), None);
+		assert_eq!(SurjectionProofInfo::from_proof(&[0x01, 0x00]), None);
+		assert!(SurjectionProofInfo::from_proof(&[0x01, 0x00, 0x01]).is_some());
+		// Nine inputs take two bitmap bytes.
+		assert_eq!(SurjectionProofInfo::from_proof(&[0x09, 0x00, 0xff]), None);
+		assert!(SurjectionProofInfo::from_proof(&[0x09, 0x00, 0xff, 0x01]).is_some());
+	}
+}
@@ -0,0 +1,415 @@
+//! Elements confidential output descriptors as specified in ELIP-150.
+//!
+//! Only the Elements counterparts of the Bitcoin `pkh`, `wpkh`, `sh` and `wsh` descriptors are
+//! supported, i.e. `elpkh`, `elwpkh`, `elsh` and `elwsh`. Taproot `eltr` descriptors are not.
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{self, Scalar};
+use bitcoin::util::bip32::ChildNumber;
+use bitcoin::PrivateKey;
+use elements::encode::Encodable;
+use elements::slip77::MasterBlindingKey;
+use elements::{Address, Script};
+use miniscript::descriptor::{
+	Descriptor, DescriptorPublicKey, DescriptorSecretKey, Wildcard,
+};
+use serde::{Deserialize, Serialize};
+
+use address::AddressInfo;
+use {Chain, GetInfo, Network};
+
+/// The tag of the hash used to tweak ELIP-150 blinding keys.
+const BLINDING_KEY_TAG: &str = "CT-Blinding-Key/1.0";
+
+/// The Elements descriptor prefixes that map onto Bitcoin descriptors.
+const ELEMENTS_PREFIXES: [&str; 4] = ["elpkh(", "elwpkh(", "elsh(", "elwsh("];
+
+/// The blinding key of a confidential descriptor.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BlindingKey {
+	/// A SLIP-77 master blinding key.
+	Slip77(MasterBlindingKey),
+	/// A public key that is tweaked with the scriptPubKey.
+	Bare(secp256k1::PublicKey),
+	/// A private key that is tweaked with the scriptPubKey.
+	View(secp256k1::SecretKey),
+}
+
+impl BlindingKey {
+	/// The ELIP-150 tweak for the given blinding pubkey and scriptPubKey.
+	fn tweak(pubkey: &secp256k1::PublicKey, spk: &Script) -> Scalar {
+		let tag = sha256::Hash::hash(BLINDING_KEY_TAG.as_bytes());
+		let mut engine = sha256::Hash::engine();
+		engine.input(&tag[..]);
+		engine.input(&tag[..]);
+		engine.input(&pubkey.serialize());
+		spk.consensus_encode(&mut engine).expect("engines don't error");
+		let hash = sha256::Hash::from_engine(engine);
+		Scalar::from_be_bytes(hash.into_inner()).expect("hash value greater than curve order")
+	}
+
+	/// The blinding private key for the scriptPubKey, if this is not a bare public key.
+	pub fn blinding_private_key(&self, spk: &Script) -> Option<secp256k1::SecretKey> {
+		let secp = secp256k1::Secp256k1::signing_only();
+		match *self {
+			BlindingKey::Slip77(ref mbk) => Some(mbk.derive_blinding_key(spk)),
+			BlindingKey::Bare(_) => None,
+			BlindingKey::View(ref sk) => {
+				let pk = secp256k1::PublicKey::from_secret_key(&secp, sk);
+				Some(sk.add_tweak(&BlindingKey::tweak(&pk, spk)).expect("tweak is valid"))
+			}
+		}
+	}
+
+	/// The blinding public key for the scriptPubKey.
+	pub fn blinding_public_key(&self, spk: &Script) -> secp256k1::PublicKey {
+		let secp = secp256k1::Secp256k1::new();
+		match *self {
+			BlindingKey::Bare(ref pk) => {
+				pk.add_exp_tweak(&secp, &BlindingKey::tweak(pk, spk)).expect("tweak is valid")
+			}
+			_ => {
+				let sk = self.blinding_private_key(spk).expect("not bare");
+				secp256k1::PublicKey::from_secret_key(&secp, &sk)
+			}
+		}
+	}
+}
+
+impl FromStr for BlindingKey {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<BlindingKey, String> {
+		let secp = secp256k1::Secp256k1::new();
+
+		if s.starts_with("slip77(") && s.ends_with(')') {
+			let sk = s["slip77(".len()..s.len() - 1]
+				.parse()
+				.map_err(|e| format!("invalid slip77 master blinding key: {}", e))?;
+			return Ok(BlindingKey::Slip77(MasterBlindingKey(sk)));
+		}
+		if s.len() == 64 {
+			if let Ok(sk) = s.parse() {
+				return Ok(BlindingKey::View(sk));
+			}
+		}
+		if let Ok(key) = PrivateKey::from_wif(s) {
+			return Ok(BlindingKey::View(key.inner));
+		}
+
+		if let Ok(DescriptorSecretKey::XPrv(xkey)) = DescriptorSecretKey::from_str(s) {
+			if xkey.wildcard != Wildcard::None {
+				return Err("blinding keys can't have wildcards".to_owned());
+			}
+			let key = xkey.xkey
+				.derive_priv(&secp, &xkey.derivation_path)
+				.map_err(|e| format!("invalid blinding key derivation: {}", e))?
+				.private_key;
+			return Ok(BlindingKey::View(key));
+		}
+
+		let pk = DescriptorPublicKey::from_str(s).map_err(|e| format!("invalid blinding key: {}", e))?;
+		if pk.has_wildcard() {
+			return Err("blinding keys can't have wildcards".to_owned());
+		}
+		let pk = pk
+			.at_derivation_index(0)
+			.derive_public_key(&secp)
+			.map_err(|e| format!("invalid blinding key derivation: {}", e))?;
+		Ok(BlindingKey::Bare(pk.inner))
+	}
+}
+
+/// A confidential descriptor of the form `ct(<blinding key>,<descriptor>)`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConfidentialDescriptor {
+	pub blinding_key: BlindingKey,
+	/// The inner descriptor, without the Elements `el` prefix.
+	pub descriptor: Descriptor<DescriptorPublicKey>,
+}
+
+impl ConfidentialDescriptor {
+	/// Whether the descriptor can be derived at multiple indices.
+	pub fn has_wildcard(&self) -> bool {
+		self.descriptor.has_wildcard()
+	}
+
+	/// The scriptPubKey of the descriptor at the given derivation index.
+	///
+	/// Hardened indices, i.e. 2^31 and higher, are not allowed.
+	pub fn script_pubkey(&self, index: u32) -> Result<Script, String> {
+		ChildNumber::from_normal_idx(index).map_err(|_| format!("invalid derivation index: {}", index))?;
+		let secp = secp256k1::Secp256k1::verification_only();
+		let derived = self.descriptor.at_derivation_index(index)
+			.derived_descriptor(&secp)
+			.map_err(|e| format!("failed to derive descriptor: {}", e))?;
+		Ok(derived.script_pubkey().to_bytes().into())
+	}
+
+	/// The confidential address of the descriptor at the given derivation index.
+	pub fn address(&self, index: u32, network: Network) -> Result<Address, String> {
+		let spk = self.script_pubkey(index)?;
+		let blinder = self.blinding_key.blinding_public_key(&spk);
+		Address::from_script(&spk, Some(blinder), network.address_params())
+			.ok_or_else(|| "descriptor has no address form".to_owned())
+	}
+
+	/// Get information about the descriptor derived at the given index.
+	pub fn derive_info(&self, index: u32, network: Network) -> Result<DerivedAddressInfo, String> {
+		let spk = self.script_pubkey(index)?;
+		let address = self.address(index, network)?;
+		Ok(DerivedAddressInfo {
+			index,
+			info: address.get_info(Chain::new(network)),
+			address,
+			blinding_private_key: self.blinding_key.blinding_private_key(&spk),
+		})
+	}
+}
+
+impl FromStr for ConfidentialDescriptor {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<ConfidentialDescriptor, String> {
+		let desc = verify_checksum(s.trim())?;
+		if !desc.starts_with("ct(") || !desc.ends_with(')') {
+			return Err("confidential descriptors have the form ct(<key>,<descriptor>)".to_owned());
+		}
+		let inner = &desc["ct(".len()..desc.len() - 1];
+
+		// The blinding key can't contain commas, so split at the first one.
+		let comma = inner.find(',').ok_or("missing descriptor in ct()")?;
+		let (key, desc) = (&inner[..comma], &inner[comma + 1..]);
+
+		if !ELEMENTS_PREFIXES.iter().any(|p| desc.starts_with(p)) {
+			return Err(format!(
+				"unsupported descriptor, must start with one of {}",
+				ELEMENTS_PREFIXES.join(", ").replace("(", ""),
+			));
+		}
+		let descriptor = desc["el".len()..]
+			.parse::<Descriptor<DescriptorPublicKey>>()
+			.map_err(|e| format!("invalid descriptor: {}", e))?;
+
+		Ok(ConfidentialDescriptor {
+			blinding_key: key.parse()?,
+			descriptor,
+		})
+	}
+}
+
+impl fmt::Display for ConfidentialDescriptor {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let key = match self.blinding_key {
+			BlindingKey::Slip77(ref mbk) => format!("slip77({})", mbk.0.display_secret()),
+			BlindingKey::Bare(ref pk) => pk.to_string(),
+			BlindingKey::View(ref sk) => sk.display_secret().to_string(),
+		};
+		// Strip the checksum of the inner descriptor.
+		let inner = self.descriptor.to_string();
+		let inner = inner.split('#').next().expect("split has at least one item");
+		let desc = format!("ct({},el{})", key, inner);
+		write!(f, "{}#{}", desc, checksum(&desc).expect("valid descriptor characters"))
+	}
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct DerivedAddressInfo {
+	pub index: u32,
+	pub address: Address,
+	#[serde(flatten)]
+	pub info: AddressInfo,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub blinding_private_key: Option<secp256k1::SecretKey>,
+}
+
+const CHECKSUM_INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn checksum_poly_mod(mut c: u64, val: u64) -> u64 {
+	let c0 = c >> 35;
+	c = ((c & 0x7ffffffff) << 5) ^ val;
+	if c0 & 1 > 0 {
+		c ^= 0xf5dee51989
+	};
+	if c0 & 2 > 0 {
+		c ^= 0xa9fdca3312
+	};
+	if c0 & 4 > 0 {
+		c ^= 0x1bab10e32d
+	};
+	if c0 & 8 > 0 {
+		c ^= 0x3706b1677a
+	};
+	if c0 & 16 > 0 {
+		c ^= 0x644d626ffd
+	};
+	c
+}
+
+/// Compute the descriptor checksum as done in Bitcoin Core.
+pub fn checksum(desc: &str) -> Result<String, String> {
+	let mut c = 1;
+	let mut cls = 0;
+	let mut clscount = 0;
+	for ch in desc.chars() {
+		let pos = CHECKSUM_INPUT_CHARSET
+			.find(ch)
+			.ok_or_else(|| format!("invalid character in descriptor: '{}'", ch))? as u64;
+		c = checksum_poly_mod(c, pos & 31);
+		cls = cls * 3 + (pos >> 5);
+		clscount += 1;
+		if clscount == 3 {
+			c = checksum_poly_mod(c, cls);
+			cls = 0;
+			clscount = 0;
+		}
+	}
+	if clscount > 0 {
+		c = checksum_poly_mod(c, cls);
+	}
+	for _ in 0..8 {
+		c = checksum_poly_mod(c, 0);
+	}
+	c ^= 1;
+
+	Ok((0..8).map(|j| CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char).collect())
+}
+
+/// Verify the checksum if one is present and return the descriptor without it.
+fn verify_checksum(s: &str) -> Result<&str, String> {
+	let mut parts = s.splitn(2, '#');
+	let desc = parts.next().expect("split has at least one item");
+	if let Some(sum) = parts.next() {
+		let expected = checksum(desc)?;
+		if sum != expected {
+			return Err(format!("invalid checksum '{}', expected '{}'", sum, expected));
+		}
+	}
+	Ok(desc)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Test vectors from ELIP-150.
+	const CT_XPUB: &str = "xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL";
+	const SPK_XPUB: &str = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+	const SLIP77: &str = "slip77(b2396b3ee20509cdb64fe24180a14a72dbd671728eaa49bac69d2bdecb5f5a04)";
+	const VIEW_XPRV: &str = "xprv9s21ZrQH143K28NgQ7bHCF61hy9VzwquBZvpzTwXLsbmQLRJ6iV9k2hUBRt5qzmBaSpeMj5LdcsHaXJvM7iFEivPryRcL8irN7Na9p65UUb";
+	const VIEW_KEY: &str = "c25deb86fa11e49d651d7eae27c220ef930fbd86ea023eebfa73e54875647963";
+	const VIEW_PUBKEY: &str = "0286fc9a38e765d955e9b0bcc18fa9ae81b0c893e2dd1ef5542a9c73780a086b90";
+	const SPK_PUBKEY: &str = "03774eec7a3d550d18e9f89414152025b3b0ad6a342b19481f702d843cff06dfc4";
+
+	fn check_vector(desc: &str, sum: &str, address: &str) {
+		assert_eq!(checksum(desc).unwrap(), sum, "{}", desc);
+		let parsed = format!("{}#{}", desc, sum).parse::<ConfidentialDescriptor>().unwrap();
+		assert_eq!(parsed.address(0, Network::Liquid).unwrap().to_string(), address, "{}", desc);
+	}
+
+	#[test]
+	fn elip150_vectors() {
+		let ct = CT_XPUB;
+		let spk = SPK_XPUB;
+		check_vector(
+			&format!("ct({},elpkh({}))", ct, spk),
+			"y0lg3d5y",
+			"VTpvZZYdbhbyVF3Wa99eMjgXhfvu4LS26dR2FwMfNXq7FDX73HZEsZr3VvgH9EDgQnYK7sP6ACKSuMGw",
+		);
+		check_vector(
+			&format!("ct({},elwpkh({}))", ct, spk),
+			"kt4e25qt",
+			"lq1qqg5s7xj7upzl7h4q2k2wj4vq63nvaktn0egqu09nqcr6d44p4evaqknpl78t02k2xqgdh9ltmfmpy9ssk7qfvghdsfr4mvr9c",
+		);
+		check_vector(
+			&format!("ct({},elsh(wpkh({})))", ct, spk),
+			"xg9r4jej",
+			"VJL8znN4XjXEUKzDaYsqdzRASGLY2KHxC4N6g5b5QvrNjXfeKp83Ci9AW2a8QzbZjpEffoy4PEywpLAZ",
+		);
+		check_vector(
+			&format!("ct({},elpkh({}))", SLIP77, spk),
+			"hw2glz99",
+			"VTq585ahVjWarEwg2nKQ9yYirmYs5F5j74CeYYA9cq1EZD9obm7hwpx6xqq3J1AY9YRaSavEMzYfr6t7",
+		);
+		check_vector(
+			&format!("ct({},elwpkh({}))", SLIP77, spk),
+			"545pl285",
+			"lq1qqdx5wnttttzulcs6ujlg9pfts6mp3r4sdwg5ekdej566n5wxzk88vknpl78t02k2xqgdh9ltmfmpy9ssk7qfvr33xa22hpw23",
+		);
+		check_vector(
+			&format!("ct({},elsh(wpkh({})))", SLIP77, spk),
+			"m30vswxr",
+			"VJLFGQ17aGa3WSVEVyxzDktD9SFixJjfSmqVq8xaWmR9X6gFbiF95KFwKA41PBhu3jNTxJFKTUphHL8J",
+		);
+		check_vector(
+			&format!("ct(02dce16018bbbb8e36de7b394df5b5166e9adb7498be7d881a85a09aeecf76b623,elwpkh({}))", SPK_PUBKEY),
+			"h5e0p6m9",
+			"lq1qq0r6pegudzm0tzpszelc34qjln4fdxawgwmgnza63wwpzdy6jrm0grmqvvk2ce5ksnxcs9ecgtnryt7xg3406y5ccl0k2glns",
+		);
+		check_vector(
+			&format!("ct(02dce16018bbbb8e36de7b394df5b5166e9adb7498be7d881a85a09aeecf76b623,elwpkh({}))", spk),
+			"x6sc2de2",
+			"lq1qqwkeuelr466ue5u8e0lz3a27q4yk93qnupry5h3q4h9pjpf8vrrzvknpl78t02k2xqgdh9ltmfmpy9ssk7qfvwt93dvuvssha",
+		);
+		check_vector(
+			&format!("ct({},elwpkh({}))", VIEW_XPRV, spk),
+			"j95xktq7",
+			"lq1qq2r0pdvcknjpwev96qu9975alzqs78cvsut5ju82t7tv8d645dgmwknpl78t02k2xqgdh9ltmfmpy9ssk7qfvtk83xqzx62q4",
+		);
+	}
+
+	#[test]
+	fn elip150_view_key() {
+		let pk = "021a8fb6bd5a653b021b98a2a785725b8ddacfe3687bc043aa7f4d25d3a48d40b5";
+		let address = "lq1qq265u4g3k3m3qpyxjwpdrtnm293wuxgvs9xzmzcs2ck0mv5rx23w4d7xfsednsmmxrszfe7s9rs0c6cvf3dfytxax3utlmm46";
+		check_vector(&format!("ct({},elwpkh({}))", VIEW_KEY, pk), "c2kx9zll", address);
+		check_vector(&format!("ct({},elwpkh({}))", VIEW_PUBKEY, pk), "m5mvyh29", address);
+
+		// The view key yields the blinding private key of the address derived from the pubkey.
+		let view = format!("ct({},elwpkh({}))", VIEW_KEY, pk).parse::<ConfidentialDescriptor>().unwrap();
+		let bare = format!("ct({},elwpkh({}))", VIEW_PUBKEY, pk).parse::<ConfidentialDescriptor>().unwrap();
+		let spk = bare.script_pubkey(0).unwrap();
+		let secp = secp256k1::Secp256k1::new();
+		let sk = view.blinding_key.blinding_private_key(&spk).unwrap();
+		assert_eq!(
+			secp256k1::PublicKey::from_secret_key(&secp, &sk),
+			bare.blinding_key.blinding_public_key(&spk),
+		);
+		assert_eq!(
+			bare.address(0, Network::Liquid).unwrap().to_unconfidential().to_string(),
+			"ex1qklrycvkecdanpcpyulgz3c8udvxyck5jvsv4j5",
+		);
+
+		// A WIF view key is the same key as its hex encoding.
+		let wif = "L3jXxwef3fpB7hcrFozcWgHeJCPSAFiZ1Ji2YJMPxceaGvy3PC1q".parse::<BlindingKey>().unwrap();
+		assert_eq!(wif, VIEW_KEY.parse::<BlindingKey>().unwrap());
+	}
+
+	#[test]
+	fn elip150_invalid() {
+		let desc = format!("ct({},elsh(wpkh({})))", SLIP77, SPK_PUBKEY);
+		assert_eq!(checksum(&desc).unwrap(), "qgjmm4as");
+		assert!(format!("{}#xxxxxxxx", desc).parse::<ConfidentialDescriptor>().is_err());
+		assert!(format!("{}#qgjmm4as", desc).parse::<ConfidentialDescriptor>().is_ok());
+
+		// ct() takes exactly two arguments.
+		let three = format!("ct({},{},elwpkh({}))", SLIP77, VIEW_KEY, SPK_PUBKEY);
+		assert!(three.parse::<ConfidentialDescriptor>().is_err());
+		let one = format!("ct(elwpkh({}))", SPK_PUBKEY);
+		assert!(one.parse::<ConfidentialDescriptor>().is_err());
+
+		// Only unhardened derivation indices are allowed.
+		let wildcard = format!("ct({},elwpkh({}/*))", SLIP77, SPK_XPUB).parse::<ConfidentialDescriptor>().unwrap();
+		assert!(wildcard.address((1 << 31) - 1, Network::Liquid).is_ok());
+		assert!(wildcard.address(1 << 31, Network::Liquid).is_err());
+		assert!(wildcard.address(u32::MAX, Network::Liquid).is_err());
+
+		// Wildcard blinding keys are not supported.
+		let wildcard = format!("ct({}/*,elwpkh({}))", CT_XPUB, SPK_PUBKEY);
+		assert!(wildcard.parse::<ConfidentialDescriptor>().is_err());
+	}
+}
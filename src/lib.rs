@@ -1,10 +1,18 @@
 extern crate elements;
 extern crate hal;
 extern crate hex;
+extern crate miniscript;
+extern crate rand;
 extern crate serde;
+extern crate serde_json;
 
 pub mod address;
+pub mod bip32;
 pub mod block;
+pub mod descriptor;
+pub mod pak;
+pub mod pegin;
+pub mod registry;
 pub mod tx;
 
 pub mod confidential;
@@ -12,23 +20,36 @@ pub mod confidential;
 pub use hal::HexBytes;
 pub use elements::bitcoin;
 
-use elements::AddressParams;
-use serde::{Deserialize, Serialize};
+use elements::{AddressParams, AssetId};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use registry::AssetRegistry;
 
 /// Known Elements networks.
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Network {
 	ElementsRegtest,
 	Liquid,
+	LiquidTestnet,
+	/// A user-defined chain with the given address parameters.
+	Custom(&'static AddressParams),
 }
 
+/// The policy asset of Liquid, L-BTC.
+pub const LIQUID_POLICY_ASSET: &str = "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d";
+/// The policy asset of Liquid testnet, tL-BTC.
+pub const LIQUID_TESTNET_POLICY_ASSET: &str = "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49";
+/// The policy asset of a default elementsregtest chain.
+pub const ELEMENTS_REGTEST_POLICY_ASSET: &str = "5ac9f65c0efcc4775e0baec4ec03abdde22473cd3cf33c0419ca290e0751b225";
+
 impl Network {
-	pub fn from_params(params: &'static AddressParams) -> Option<Network> {
+	/// The network of the address params, params of unknown networks are custom.
+	pub fn from_params(params: &'static AddressParams) -> Network {
 		match params {
-			&AddressParams::ELEMENTS => Some(Network::ElementsRegtest),
-			&AddressParams::LIQUID => Some(Network::Liquid),
-			_ => None,
+			&AddressParams::ELEMENTS => Network::ElementsRegtest,
+			&AddressParams::LIQUID => Network::Liquid,
+			&AddressParams::LIQUID_TESTNET => Network::LiquidTestnet,
+			p => Network::Custom(p),
 		}
 	}
 
@@ -36,12 +57,105 @@ impl Network {
 		match self {
 			Network::ElementsRegtest => &AddressParams::ELEMENTS,
 			Network::Liquid => &AddressParams::LIQUID,
+			Network::LiquidTestnet => &AddressParams::LIQUID_TESTNET,
+			Network::Custom(params) => params,
+		}
+	}
+
+	/// The name of the network as used in JSON output.
+	pub fn name(self) -> &'static str {
+		match self {
+			Network::ElementsRegtest => "elementsregtest",
+			Network::Liquid => "liquid",
+			Network::LiquidTestnet => "liquidtestnet",
+			Network::Custom(_) => "custom",
+		}
+	}
+
+	/// The Bitcoin network the network pegs in from, unknown for custom chains.
+	pub fn mainchain_network(self) -> Option<bitcoin::Network> {
+		match self {
+			Network::ElementsRegtest => Some(bitcoin::Network::Regtest),
+			Network::Liquid => Some(bitcoin::Network::Bitcoin),
+			Network::LiquidTestnet => Some(bitcoin::Network::Testnet),
+			Network::Custom(_) => None,
+		}
+	}
+
+	/// The default policy asset of the network, if it is known.
+	pub fn policy_asset(self) -> Option<AssetId> {
+		let parse = |id: &str| id.parse().expect("valid asset id");
+		match self {
+			Network::Liquid => Some(parse(LIQUID_POLICY_ASSET)),
+			Network::LiquidTestnet => Some(parse(LIQUID_TESTNET_POLICY_ASSET)),
+			Network::ElementsRegtest => Some(parse(ELEMENTS_REGTEST_POLICY_ASSET)),
+			Network::Custom(_) => None,
+		}
+	}
+}
+
+impl Serialize for Network {
+	fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_str(self.name())
+	}
+}
+
+impl<'de> Deserialize<'de> for Network {
+	fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Network, D::Error> {
+		let name = String::deserialize(d)?;
+		match name.as_str() {
+			"elementsregtest" => Ok(Network::ElementsRegtest),
+			"liquid" => Ok(Network::Liquid),
+			"liquidtestnet" => Ok(Network::LiquidTestnet),
+			"custom" => Err(de::Error::custom("custom networks can't be deserialized without their parameters")),
+			_ => Err(de::Error::unknown_variant(&name, &["elementsregtest", "liquid", "liquidtestnet"])),
+		}
+	}
+}
+
+/// The chain parameters of a user-defined Elements chain.
+///
+/// This is the format of chain profile files.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct ChainParams {
+	/// The base58 prefix for p2pkh addresses.
+	pub p2pkh_prefix: u8,
+	/// The base58 prefix for p2sh addresses.
+	pub p2sh_prefix: u8,
+	/// The base58 prefix for blinded addresses.
+	pub blinded_prefix: u8,
+	/// The bech32 HRP for unblinded segwit addresses.
+	pub bech_hrp: String,
+	/// The blech32 HRP for blinded segwit addresses.
+	pub blech_hrp: String,
+	/// The policy asset of the chain, used to pay fees.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub policy_asset: Option<AssetId>,
+}
+
+/// The chain that objects are described for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chain<'a> {
+	pub network: Network,
+	/// The policy asset of the chain, if it is known.
+	pub policy_asset: Option<AssetId>,
+	/// The registry to take asset metadata from, if any.
+	pub registry: Option<&'a AssetRegistry>,
+}
+
+impl<'a> Chain<'a> {
+	/// The chain of the network with its default policy asset and no registry.
+	pub fn new(network: Network) -> Chain<'a> {
+		Chain {
+			network,
+			policy_asset: network.policy_asset(),
+			registry: None,
 		}
 	}
 }
 
 /// Get JSON-able objects that describe the type.
 pub trait GetInfo<T: ::serde::Serialize> {
-	/// Get a description of this object given the network of interest.
-	fn get_info(&self, network: Network) -> T;
+	/// Get a description of this object given the chain of interest.
+	fn get_info(&self, chain: Chain) -> T;
 }
@@ -0,0 +1,174 @@
+//! Peg-out authorization keys (PAK) as used by Liquid.
+//!
+//! The PAK list is committed in the extension space of the dynafed parameters,
+//! with every entry the concatenation of an offline and an online key. Peg-outs
+//! prove that their key is whitelisted by the list with a whitelist signature
+//! that is pushed in the peg-out script after the mainchain scriptPubKey.
+
+use bitcoin::BlockHash;
+use elements::secp256k1_zkp::{self, PublicKey, WhitelistSignature};
+use elements::PegoutData;
+use serde::{Deserialize, Serialize};
+
+use HexBytes;
+
+/// An entry in a PAK list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PakEntryInfo {
+	pub offline_key: PublicKey,
+	pub online_key: PublicKey,
+}
+
+/// Parse a PAK list from the extension space of dynafed parameters.
+///
+/// Like in Elements, a single entry that isn't a pair of keys invalidates the list.
+pub fn pak_list_from_extension_space<T: AsRef<[u8]>>(extension_space: &[T]) -> Result<Vec<PakEntryInfo>, String> {
+	extension_space.iter().enumerate().map(|(i, entry)| {
+		let entry = entry.as_ref();
+		if entry.len() != 66 {
+			return Err(format!("PAK entry {} has {} bytes instead of 66", i, entry.len()));
+		}
+		Ok(PakEntryInfo {
+			offline_key: PublicKey::from_slice(&entry[0..33])
+				.map_err(|e| format!("invalid offline key in PAK entry {}: {}", i, e))?,
+			online_key: PublicKey::from_slice(&entry[33..66])
+				.map_err(|e| format!("invalid online key in PAK entry {}: {}", i, e))?,
+		})
+	}).collect()
+}
+
+/// Parse a PAK entry given like Elements' `-pak` option: `<offline key>:<online key>`.
+pub fn parse_pak_entry(s: &str) -> Result<PakEntryInfo, String> {
+	let mut parts = s.splitn(2, ':');
+	let offline = parts.next().expect("split has at least one item");
+	let online = parts.next().ok_or("PAK entries have the form <offline key>:<online key>")?;
+	Ok(PakEntryInfo {
+		offline_key: offline.parse().map_err(|e| format!("invalid offline key: {}", e))?,
+		online_key: online.parse().map_err(|e| format!("invalid online key: {}", e))?,
+	})
+}
+
+/// The PAK proof of a peg-out.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PakProofInfo {
+	/// The key that is proven to be whitelisted by the PAK list.
+	pub whitelist_key: PublicKey,
+	pub proof: HexBytes,
+	/// The size of the PAK list the proof is for.
+	pub n_keys: usize,
+}
+
+/// Parse the PAK proof from the extra data of a peg-out.
+pub fn pak_proof<T: AsRef<[u8]>>(extra_data: &[T]) -> Result<PakProofInfo, String> {
+	if extra_data.len() != 2 {
+		return Err(format!("peg-out has {} extra data pushes instead of a key and a proof", extra_data.len()));
+	}
+	let key = PublicKey::from_slice(extra_data[0].as_ref())
+		.map_err(|e| format!("invalid whitelist key: {}", e))?;
+	let proof = WhitelistSignature::from_slice(extra_data[1].as_ref())
+		.map_err(|e| format!("invalid whitelist proof: {}", e))?;
+	Ok(PakProofInfo {
+		whitelist_key: key,
+		proof: extra_data[1].as_ref().into(),
+		n_keys: proof.n_keys(),
+	})
+}
+
+/// Verify the PAK proof of the peg-out against the PAK list.
+///
+/// The peg-out must also be for the parent chain with the given genesis block hash.
+pub fn verify_pak_proof(
+	pegout: &PegoutData,
+	pak_list: &[PakEntryInfo],
+	parent_genesis_hash: BlockHash,
+) -> Result<(), String> {
+	if pegout.genesis_hash != parent_genesis_hash {
+		return Err(format!(
+			"peg-out is for the chain with genesis block {} instead of {}",
+			pegout.genesis_hash, parent_genesis_hash,
+		));
+	}
+
+	let spk = &pegout.script_pubkey;
+	if !spk.is_p2pkh() && !spk.is_p2sh() && !spk.is_v0_p2wpkh() && !spk.is_v0_p2wsh() {
+		return Err("PAK peg-outs can only pay to p2pkh, p2sh, p2wpkh or p2wsh".to_owned());
+	}
+
+	let proof = pak_proof(&pegout.extra_data)?;
+	if proof.n_keys != pak_list.len() {
+		return Err(format!("proof is for {} keys, but the PAK list has {}", proof.n_keys, pak_list.len()));
+	}
+	let sig = WhitelistSignature::from_slice(&proof.proof.0).expect("parsed before");
+	let offline_keys = pak_list.iter().map(|e| e.offline_key).collect::<Vec<_>>();
+	let online_keys = pak_list.iter().map(|e| e.online_key).collect::<Vec<_>>();
+	let secp = secp256k1_zkp::Secp256k1::verification_only();
+	sig.verify(&secp, &online_keys, &offline_keys, &proof.whitelist_key)
+		.map_err(|e| format!("invalid PAK proof: {}", e))
+}
+
+/// The result of verifying the PAK proof of a peg-out output.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PakVerificationInfo {
+	pub index: usize,
+	pub valid: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use bitcoin::hashes::Hash;
+	use bitcoin::{PubkeyHash, Script};
+	use elements::confidential;
+	use elements::secp256k1_zkp::{Scalar, Secp256k1, SecretKey};
+
+	#[test]
+	fn pak_proof_roundtrip() {
+		let secp = Secp256k1::new();
+		let sk = |b: u8| SecretKey::from_slice(&[b; 32]).unwrap();
+		let pk = |sk: &SecretKey| PublicKey::from_secret_key(&secp, sk);
+
+		let offline = [sk(1), sk(2)];
+		let online = [sk(3), sk(4)];
+		let extension_space = (0..2).map(|i| {
+			let mut entry = pk(&offline[i]).serialize().to_vec();
+			entry.extend(&pk(&online[i]).serialize());
+			entry
+		}).collect::<Vec<_>>();
+		let pak_list = pak_list_from_extension_space(&extension_space).unwrap();
+		assert_eq!(pak_list[1], PakEntryInfo { offline_key: pk(&offline[1]), online_key: pk(&online[1]) });
+
+		// Sign for the key tweaked from the second PAK entry.
+		let whitelist_sk = sk(9);
+		let summed = offline[1].add_tweak(&Scalar::from(whitelist_sk)).unwrap();
+		let offline_keys = pak_list.iter().map(|e| e.offline_key).collect::<Vec<_>>();
+		let online_keys = pak_list.iter().map(|e| e.online_key).collect::<Vec<_>>();
+		let sig = WhitelistSignature::new(
+			&secp, &online_keys, &offline_keys, &pk(&whitelist_sk), &online[1], &summed, 1,
+		).unwrap();
+
+		let key = pk(&whitelist_sk).serialize();
+		let proof_bytes = sig.serialize();
+		let proof = pak_proof(&[&key[..], &proof_bytes[..]]).unwrap();
+		assert_eq!(proof.whitelist_key, pk(&whitelist_sk));
+		assert_eq!(proof.n_keys, 2);
+
+		let genesis_hash = BlockHash::hash(b"genesis");
+		let pegout = PegoutData {
+			value: 1000,
+			asset: confidential::Asset::Null,
+			genesis_hash,
+			script_pubkey: Script::new_p2pkh(&PubkeyHash::hash(&key)),
+			extra_data: vec![&key[..], &proof_bytes[..]],
+		};
+		assert_eq!(verify_pak_proof(&pegout, &pak_list, genesis_hash), Ok(()));
+		assert!(verify_pak_proof(&pegout, &pak_list, BlockHash::hash(b"other")).is_err());
+		assert!(verify_pak_proof(&pegout, &pak_list[..1], genesis_hash).is_err());
+
+		let mut three = pak_list.clone();
+		three.push(pak_list[0]);
+		assert!(verify_pak_proof(&pegout, &three, genesis_hash).is_err());
+	}
+}
@@ -0,0 +1,507 @@
+//! Peg-ins from the Bitcoin mainchain.
+//!
+//! Peg-in outputs on the mainchain pay to the federation's fedpegscript with
+//! every federation key tweaked with the claim script of the sidechain output.
+
+use bitcoin::blockdata::opcodes::all::{OP_DEPTH, OP_ELSE};
+use bitcoin::blockdata::script::{Builder, Instruction};
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{self, Scalar};
+use bitcoin::{self, Address, BlockHash, BlockHeader, MerkleBlock, Script, Transaction};
+use elements::encode::serialize;
+use elements::{AssetId, PeginData, TxIn, TxInWitness};
+use serde::{Deserialize, Serialize};
+
+use HexBytes;
+
+/// Tweak the federation keys in the fedpegscript with the claim script.
+///
+/// Every key is tweaked with the HMAC-SHA256 of the claim script keyed by the
+/// key itself. In Liquid's watchman script, which starts with `OP_DEPTH`, the
+/// emergency keys after the `OP_ELSE` are not tweaked.
+pub fn tweak_fedpeg_script(fedpeg_script: &Script, claim_script: &[u8]) -> Result<Script, String> {
+	let secp = secp256k1::Secp256k1::verification_only();
+	let liquid = fedpeg_script.as_bytes().first() == Some(&OP_DEPTH.to_u8());
+
+	let mut builder = Builder::new();
+	let mut op_else_seen = false;
+	for ins in fedpeg_script.instructions() {
+		match ins.map_err(|e| format!("invalid fedpegscript: {}", e))? {
+			Instruction::Op(op) => {
+				op_else_seen |= liquid && op == OP_ELSE;
+				builder = builder.push_opcode(op);
+			}
+			Instruction::PushBytes(data) if data.len() == 33 && !op_else_seen => {
+				let key = secp256k1::PublicKey::from_slice(data)
+					.map_err(|e| format!("invalid key in fedpegscript: {}", e))?;
+				let mut engine = hmac::HmacEngine::<sha256::Hash>::new(data);
+				engine.input(claim_script);
+				let tweak = hmac::Hmac::<sha256::Hash>::from_engine(engine);
+				let tweak = Scalar::from_be_bytes(tweak.into_inner())
+					.map_err(|_| "tweak value greater than curve order".to_owned())?;
+				let tweaked = key.add_exp_tweak(&secp, &tweak).map_err(|e| format!("invalid tweak: {}", e))?;
+				builder = builder.push_slice(&tweaked.serialize());
+			}
+			Instruction::PushBytes(data) => builder = builder.push_slice(data),
+		}
+	}
+	Ok(builder.into_script())
+}
+
+/// The mainchain scriptPubKeys that a peg-in with the claim script can pay to.
+///
+/// Both the wrapped p2sh-p2wsh form used by peg-in addresses and the native
+/// p2wsh form are accepted by the sidechain.
+pub fn pegin_script_pubkeys(fedpeg_script: &Script, claim_script: &[u8]) -> Result<Vec<Script>, String> {
+	let witness_script = tweak_fedpeg_script(fedpeg_script, claim_script)?;
+	let p2wsh = witness_script.to_v0_p2wsh();
+	Ok(vec![p2wsh.to_p2sh(), p2wsh])
+}
+
+/// A mainchain peg-in deposit address, like elementsd's `getpeginaddress` gives.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PeginAddressInfo {
+	/// The p2sh-p2wsh deposit address.
+	pub mainchain_address: Address,
+	/// The native p2wsh deposit address.
+	pub p2wsh_mainchain_address: Address,
+	/// The claim script that has to be provided when claiming the peg-in.
+	pub claim_script: HexBytes,
+	/// The fedpegscript tweaked with the claim script.
+	pub witness_script: HexBytes,
+}
+
+/// The mainchain deposit addresses for a peg-in with the claim script.
+pub fn pegin_address(
+	fedpeg_script: &Script,
+	claim_script: &[u8],
+	network: bitcoin::Network,
+) -> Result<PeginAddressInfo, String> {
+	let witness_script = tweak_fedpeg_script(fedpeg_script, claim_script)?;
+	Ok(PeginAddressInfo {
+		mainchain_address: Address::p2shwsh(&witness_script, network),
+		p2wsh_mainchain_address: Address::p2wsh(&witness_script, network),
+		claim_script: claim_script.into(),
+		witness_script: witness_script.into_bytes().into(),
+	})
+}
+
+/// Create a peg-in input that claims the output of the mainchain tx paying to the
+/// peg-in address of the claim script.
+///
+/// The merkle proof is a serialized merkle block, as given by `gettxoutproof`.
+pub fn pegin_input(
+	mainchain_tx: &[u8],
+	merkle_proof: &[u8],
+	fedpeg_script: &Script,
+	claim_script: &[u8],
+	asset: AssetId,
+	genesis_hash: BlockHash,
+) -> Result<TxIn, String> {
+	let tx = deserialize::<Transaction>(mainchain_tx).map_err(|e| format!("invalid mainchain tx: {}", e))?;
+	let merkle_block = deserialize::<MerkleBlock>(merkle_proof)
+		.map_err(|e| format!("invalid merkle proof: {}", e))?;
+	let (mut matches, mut indexes) = (Vec::new(), Vec::new());
+	merkle_block.extract_matches(&mut matches, &mut indexes)
+		.map_err(|e| format!("invalid merkle proof: {:?}", e))?;
+	if !matches.contains(&tx.txid()) {
+		return Err(format!("merkle proof doesn't commit to mainchain tx {}", tx.txid()));
+	}
+
+	let spks = pegin_script_pubkeys(fedpeg_script, claim_script)?;
+	let vout = tx.output.iter().position(|o| spks.contains(&o.script_pubkey))
+		.ok_or("mainchain tx has no output paying to the peg-in address of the claim script")?;
+
+	let txid = elements::Txid::from_inner(tx.txid().into_inner());
+	Ok(TxIn {
+		previous_output: elements::OutPoint::new(txid, vout as u32),
+		is_pegin: true,
+		witness: TxInWitness {
+			pegin_witness: vec![
+				serialize(&tx.output[vout].value),
+				serialize(&asset),
+				serialize(&genesis_hash),
+				claim_script.to_vec(),
+				mainchain_tx.to_vec(),
+				merkle_proof.to_vec(),
+			],
+			..Default::default()
+		},
+		..Default::default()
+	})
+}
+
+/// The result of verifying the mainchain proof of a peg-in input.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct PeginVerificationInfo {
+	pub index: usize,
+	/// Whether the peg-in data is valid, which requires all checks to be done.
+	pub valid: bool,
+	/// Whether the merkle proof links the mainchain tx to the referenced block.
+	pub merkle_proof: bool,
+	/// Whether the referenced block header matches the given header, if one was given.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub block_header: Option<bool>,
+	/// Whether the mainchain output pays the claimed value.
+	pub value: bool,
+	/// Whether the mainchain output pays to the tweaked fedpegscript, if one was given.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub script_pubkey: Option<bool>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<String>,
+	/// The checks that couldn't be done for lack of a block header or fedpegscript.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub unverified: Vec<String>,
+}
+
+/// Verify the mainchain tx and merkle proof of the peg-in in the given input.
+///
+/// The referenced block header is only checked against a locally provided header,
+/// the header in the merkle proof is not trusted for anything but the merkle root.
+/// Without the header or the fedpegscript, the respective checks are reported as
+/// unverified and the peg-in is not considered valid.
+pub fn verify_pegin(
+	index: usize,
+	pegin: &PeginData,
+	header: Option<&BlockHeader>,
+	fedpeg_script: Option<&Script>,
+) -> PeginVerificationInfo {
+	let mut errors = Vec::new();
+
+	let tx = match deserialize::<Transaction>(pegin.tx) {
+		Ok(tx) => Some(tx),
+		Err(e) => {
+			errors.push(format!("invalid mainchain tx: {}", e));
+			None
+		}
+	};
+	if let Some(ref tx) = tx {
+		if tx.txid() != pegin.outpoint.txid {
+			errors.push(format!("mainchain tx has txid {}, but peg-in spends {}", tx.txid(), pegin.outpoint));
+		}
+	}
+
+	let merkle_block = match deserialize::<MerkleBlock>(pegin.merkle_proof) {
+		Ok(mb) => Some(mb),
+		Err(e) => {
+			errors.push(format!("invalid merkle proof: {}", e));
+			None
+		}
+	};
+	let merkle_proof = merkle_block.as_ref().map(|mb| {
+		let (mut matches, mut indexes) = (Vec::new(), Vec::new());
+		if let Err(e) = mb.extract_matches(&mut matches, &mut indexes) {
+			errors.push(format!("invalid merkle proof: {:?}", e));
+			return false;
+		}
+		if matches != [pegin.outpoint.txid] {
+			errors.push("merkle proof doesn't commit to only the mainchain tx".to_owned());
+			return false;
+		}
+		if mb.header.block_hash() != pegin.referenced_block {
+			errors.push(format!(
+				"merkle proof is for block {}, not the referenced block",
+				mb.header.block_hash(),
+			));
+			return false;
+		}
+		true
+	}).unwrap_or(false);
+
+	let block_header = header.map(|h| {
+		if h.block_hash() != pegin.referenced_block {
+			errors.push(format!("block header has hash {}, not the referenced block", h.block_hash()));
+			false
+		} else if merkle_block.as_ref().map(|mb| mb.header.merkle_root) != Some(h.merkle_root) {
+			errors.push("merkle proof doesn't match the merkle root of the block header".to_owned());
+			false
+		} else {
+			true
+		}
+	});
+
+	let output = tx.as_ref().and_then(|tx| tx.output.get(pegin.outpoint.vout as usize));
+	if tx.is_some() && output.is_none() {
+		errors.push(format!("mainchain tx has no output {}", pegin.outpoint.vout));
+	}
+	let value = output.map(|o| {
+		if o.value != pegin.value {
+			errors.push(format!("mainchain output has value {}, but {} is claimed", o.value, pegin.value));
+		}
+		o.value == pegin.value
+	}).unwrap_or(false);
+
+	let script_pubkey = fedpeg_script.map(|fedpeg| {
+		let expected = match pegin_script_pubkeys(fedpeg, pegin.claim_script) {
+			Ok(spks) => spks,
+			Err(e) => {
+				errors.push(e);
+				return false;
+			}
+		};
+		let valid = output.map(|o| expected.contains(&o.script_pubkey)).unwrap_or(false);
+		if output.is_some() && !valid {
+			errors.push("mainchain output doesn't pay to the fedpegscript tweaked with the claim script".to_owned());
+		}
+		valid
+	});
+
+	let mut unverified = Vec::new();
+	if block_header.is_none() {
+		unverified.push("no block header given to check the referenced block".to_owned());
+	}
+	if script_pubkey.is_none() {
+		unverified.push("no fedpegscript given to check the mainchain output script".to_owned());
+	}
+
+	PeginVerificationInfo {
+		index,
+		valid: errors.is_empty() && unverified.is_empty(),
+		merkle_proof,
+		block_header,
+		value,
+		script_pubkey,
+		errors,
+		unverified,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use bitcoin::blockdata::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKSIG, OP_ENDIF, OP_EQUAL, OP_IF};
+	use bitcoin::hashes::hex::FromHex;
+	use bitcoin::{Block, OutPoint, PackedLockTime, Sequence, TxMerkleNode, TxOut, Witness};
+
+	const CLAIM_SCRIPT: &str = "00141ab7f5995cf0dfcb90cbb02b63397e5326eae6fe";
+
+	/// A peg-in from the Liquid integration tests, as used in the elements crate.
+	const REGTEST_PEGIN_TX: &str =
+		"0200000001013fe9fcf1d5eae66a152efa45ad32baa5eed3cf11ab5e04edde65\
+		 0313b58ed8c90000004000ffffffff0201f80bb0038f482243202f0b2dcf88d9\
+		 b4e7f930a48a3fcdc003af76b1f9d60e63010000000005f5c88c001976a914d7\
+		 cc0ea6d5e53af78c7802101519cc100692668e88ac01f80bb0038f482243202f\
+		 0b2dcf88d9b4e7f930a48a3fcdc003af76b1f9d60e6301000000000000187400\
+		 0000000000000002473044022048cf10f12a31cb0ec36ba3a6f79fad7e0dea3f\
+		 1aa790a5aed02f8e8455c8cb1502201a2624089ce70c893dfd07a156ba91223e\
+		 dd5680cbd93d3336285ceefcb3dc1401210205914becd15ac5d2f72ad0aa42e8\
+		 4349c825a544d8c16e78ecc21534ef561fd4060800e1f5050000000020f80bb0\
+		 038f482243202f0b2dcf88d9b4e7f930a48a3fcdc003af76b1f9d60e63200622\
+		 6e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f1600\
+		 141ab7f5995cf0dfcb90cbb02b63397e5326eae6febe020000000113244fa59f\
+		 cb407124038ff9121ed546f6dc217571cb366a50d3193f2c80298c0000000049\
+		 483045022100d1e212715d2dcbc1c66d76f43d9f326f54ff339b565c68f046ed\
+		 74040730433b02201d9ccbad57566100a06b4be47a4c777cbd7c99e0a08e17f7\
+		 bf10458117426cd801feffffff0200e1f5050000000017a914774b87be1ef871\
+		 d82a01edbb89a70bf4bb59310387a88c8b44000000001976a914b14b73956239\
+		 21dbbce438f4fc1fc8f1a495affa88acf4010000b700000020a060086af92ac3\
+		 4dbbc8bd89bbbe03ef7e0016930f7fdc806ff15d163b5fda5e32105949c74822\
+		 2d3e1c5b6e0a4d47f8de45b25d63f145c4056682a7b15cc3da56a2815bffff7f\
+		 20000000000300000003946c969d81a3b0ca473ab54c11fa665234d6ce1ad09e\
+		 87a1dbc56eb6de4002b83fe9fcf1d5eae66a152efa45ad32baa5eed3cf11ab5e\
+		 04edde650313b58ed8c9fccdc0d07eaf48f928fecfc07707b95769704d25f855\
+		 529711ed6450cc9b3c95010b00000000";
+
+	fn key(b: u8) -> secp256k1::PublicKey {
+		let secp = secp256k1::Secp256k1::signing_only();
+		secp256k1::PublicKey::from_secret_key(&secp, &secp256k1::SecretKey::from_slice(&[b; 32]).unwrap())
+	}
+
+	/// The key tweaked as in Elements' calculate_contract.
+	fn tweaked(key: &secp256k1::PublicKey, claim_script: &[u8]) -> secp256k1::PublicKey {
+		let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&key.serialize());
+		engine.input(claim_script);
+		let tweak = hmac::Hmac::<sha256::Hash>::from_engine(engine).into_inner();
+		let secp = secp256k1::Secp256k1::signing_only();
+		let tweak = secp256k1::SecretKey::from_slice(&tweak).unwrap();
+		key.combine(&secp256k1::PublicKey::from_secret_key(&secp, &tweak)).unwrap()
+	}
+
+	fn multisig_fedpeg(keys: &[secp256k1::PublicKey]) -> Script {
+		let mut builder = Builder::new().push_int(keys.len() as i64);
+		for key in keys {
+			builder = builder.push_slice(&key.serialize());
+		}
+		builder.push_int(keys.len() as i64).push_opcode(OP_CHECKMULTISIG).into_script()
+	}
+
+	#[test]
+	fn tweak_multisig_fedpeg_script() {
+		let claim_script = Vec::<u8>::from_hex(CLAIM_SCRIPT).unwrap();
+		let keys = [key(1), key(2)];
+		let tweaked_keys = keys.iter().map(|k| tweaked(k, &claim_script)).collect::<Vec<_>>();
+
+		let script = tweak_fedpeg_script(&multisig_fedpeg(&keys), &claim_script).unwrap();
+		assert_eq!(script, multisig_fedpeg(&tweaked_keys));
+
+		// The tweak commits to the claim script.
+		let other = tweak_fedpeg_script(&multisig_fedpeg(&keys), &[0x51]).unwrap();
+		assert_ne!(script, other);
+	}
+
+	#[test]
+	fn tweak_liquid_fedpeg_script() {
+		let claim_script = Vec::<u8>::from_hex(CLAIM_SCRIPT).unwrap();
+		let watchman = |online: &secp256k1::PublicKey, emergency: &secp256k1::PublicKey| {
+			Builder::new()
+				.push_opcode(OP_DEPTH).push_int(1).push_opcode(OP_EQUAL)
+				.push_opcode(OP_IF)
+				.push_slice(&online.serialize()).push_opcode(OP_CHECKSIG)
+				.push_opcode(OP_ELSE)
+				.push_slice(&emergency.serialize()).push_opcode(OP_CHECKSIG)
+				.push_opcode(OP_ENDIF)
+				.into_script()
+		};
+
+		// The emergency key after the OP_ELSE is not tweaked.
+		let script = tweak_fedpeg_script(&watchman(&key(1), &key(2)), &claim_script).unwrap();
+		assert_eq!(script, watchman(&tweaked(&key(1), &claim_script), &key(2)));
+
+		// Without the leading OP_DEPTH, all keys are tweaked.
+		let mut plain = watchman(&key(1), &key(2)).into_bytes();
+		plain.remove(0);
+		let mut expected = watchman(&tweaked(&key(1), &claim_script), &tweaked(&key(2), &claim_script)).into_bytes();
+		expected.remove(0);
+		assert_eq!(tweak_fedpeg_script(&plain.into(), &claim_script).unwrap(), Script::from(expected));
+	}
+
+	#[test]
+	fn pegin_address_forms() {
+		let claim_script = Vec::<u8>::from_hex(CLAIM_SCRIPT).unwrap();
+
+		// The default elementsregtest fedpegscript OP_TRUE has no keys to tweak.
+		let op_true = Script::from(vec![0x51]);
+		let info = pegin_address(&op_true, &claim_script, bitcoin::Network::Regtest).unwrap();
+		assert_eq!(info.witness_script.0, vec![0x51]);
+		assert_eq!(
+			info.mainchain_address.script_pubkey().as_bytes(),
+			&Vec::<u8>::from_hex("a91472c44f957fc011d97e3406667dca5b1c930c402687").unwrap()[..],
+		);
+		assert_eq!(info.claim_script.0, claim_script);
+
+		let fedpeg = multisig_fedpeg(&[key(1), key(2)]);
+		let info = pegin_address(&fedpeg, &claim_script, bitcoin::Network::Bitcoin).unwrap();
+		let spks = pegin_script_pubkeys(&fedpeg, &claim_script).unwrap();
+		assert_eq!(spks, vec![info.mainchain_address.script_pubkey(), info.p2wsh_mainchain_address.script_pubkey()]);
+		assert_eq!(Script::from(info.witness_script.0.clone()).to_v0_p2wsh(), spks[1]);
+	}
+
+	#[test]
+	fn verify_regtest_pegin() {
+		let tx: elements::Transaction = elements::encode::deserialize(
+			&Vec::<u8>::from_hex(REGTEST_PEGIN_TX).unwrap(),
+		).unwrap();
+		let pegin = tx.input[0].pegin_data().unwrap();
+		let header: BlockHeader = deserialize(&pegin.merkle_proof[0..80]).unwrap();
+
+		let info = verify_pegin(0, &pegin, Some(&header), None);
+		assert!(info.merkle_proof && info.value);
+		assert_eq!(info.block_header, Some(true));
+		assert!(info.errors.is_empty());
+		// The fedpegscript of the test isn't known, so the peg-in can't be valid.
+		assert!(!info.valid);
+		assert_eq!(info.unverified.len(), 1);
+
+		let info = verify_pegin(0, &pegin, None, None);
+		assert!(!info.valid);
+		assert_eq!(info.unverified.len(), 2);
+	}
+
+	/// A mainchain block with a coinbase and a tx paying the value to the scriptPubKey.
+	fn mainchain_block(script_pubkey: Script, value: u64) -> Block {
+		let txin = |previous_output| bitcoin::TxIn {
+			previous_output,
+			script_sig: Script::new(),
+			sequence: Sequence::MAX,
+			witness: Witness::new(),
+		};
+		let coinbase = Transaction {
+			version: 2,
+			lock_time: PackedLockTime(0),
+			input: vec![txin(OutPoint::null())],
+			output: vec![TxOut { value: 0, script_pubkey: Script::new() }],
+		};
+		let tx = Transaction {
+			version: 2,
+			lock_time: PackedLockTime(0),
+			input: vec![txin(OutPoint::new(coinbase.txid(), 0))],
+			output: vec![
+				TxOut { value: 5000, script_pubkey: Script::new_op_return(&[]) },
+				TxOut { value, script_pubkey },
+			],
+		};
+		let mut block = Block {
+			header: BlockHeader {
+				version: 0x20000000,
+				prev_blockhash: BlockHash::all_zeros(),
+				merkle_root: TxMerkleNode::all_zeros(),
+				time: 0,
+				bits: 0x207fffff,
+				nonce: 0,
+			},
+			txdata: vec![coinbase, tx],
+		};
+		block.header.merkle_root = block.compute_merkle_root().unwrap();
+		block
+	}
+
+	#[test]
+	fn pegin_input_roundtrip() {
+		let claim_script = Vec::<u8>::from_hex(CLAIM_SCRIPT).unwrap();
+		let fedpeg = multisig_fedpeg(&[key(1), key(2)]);
+		let spk = pegin_script_pubkeys(&fedpeg, &claim_script).unwrap()[0].clone();
+		let block = mainchain_block(spk, 100_000);
+		let tx = &block.txdata[1];
+		let proof = MerkleBlock::from_block_with_predicate(&block, |t| *t == tx.txid());
+		let asset = AssetId::from_inner(sha256::Midstate([1; 32]));
+		let genesis_hash = BlockHash::hash(b"genesis");
+
+		let raw_tx = bitcoin::consensus::serialize(tx);
+		let raw_proof = bitcoin::consensus::serialize(&proof);
+		let txin = pegin_input(&raw_tx, &raw_proof, &fedpeg, &claim_script, asset, genesis_hash).unwrap();
+		assert_eq!(txin.previous_output.vout, 1);
+		let verify = |txin: &TxIn, header: &BlockHeader, fedpeg: &Script| {
+			verify_pegin(0, &txin.pegin_data().unwrap(), Some(header), Some(fedpeg))
+		};
+
+		let info = verify(&txin, &block.header, &fedpeg);
+		assert!(info.valid, "{:?}", info);
+		assert_eq!(info.script_pubkey, Some(true));
+		let pegin = txin.pegin_data().unwrap();
+		assert_eq!((pegin.value, pegin.asset, pegin.genesis_hash), (100_000, asset, genesis_hash));
+
+		// The peg-in has to spend the mainchain tx.
+		let mut wrong_txid = txin.clone();
+		wrong_txid.previous_output.txid = elements::Txid::all_zeros();
+		let info = verify(&wrong_txid, &block.header, &fedpeg);
+		assert!(!info.valid && !info.merkle_proof);
+
+		// The claimed value has to match the output.
+		let mut wrong_value = txin.clone();
+		wrong_value.witness.pegin_witness[0] = serialize(&100_001u64);
+		let info = verify(&wrong_value, &block.header, &fedpeg);
+		assert!(!info.valid && !info.value);
+
+		// The merkle proof has to commit to the header's merkle root.
+		let mut wrong_root = proof.clone();
+		wrong_root.header.merkle_root = TxMerkleNode::all_zeros();
+		let mut bad_proof = txin.clone();
+		bad_proof.witness.pegin_witness[5] = bitcoin::consensus::serialize(&wrong_root);
+		let info = verify(&bad_proof, &block.header, &fedpeg);
+		assert!(!info.valid && !info.merkle_proof);
+		assert_eq!(info.block_header, Some(false));
+
+		// The output has to pay to this fedpegscript.
+		let info = verify(&txin, &block.header, &multisig_fedpeg(&[key(3)]));
+		assert!(!info.valid);
+		assert_eq!(info.script_pubkey, Some(false));
+
+		// Peg-in inputs can only be created for matching proofs and outputs.
+		let coinbase_proof = MerkleBlock::from_block_with_predicate(&block, |t| *t == block.txdata[0].txid());
+		let raw_coinbase_proof = bitcoin::consensus::serialize(&coinbase_proof);
+		assert!(pegin_input(&raw_tx, &raw_coinbase_proof, &fedpeg, &claim_script, asset, genesis_hash).is_err());
+		assert!(pegin_input(&raw_tx, &raw_proof, &fedpeg, &[0x51], asset, genesis_hash).is_err());
+	}
+}
@@ -0,0 +1,220 @@
+//! A local asset registry with asset metadata.
+//!
+//! Registry files use the format of the Blockstream asset registry: either its
+//! index, which maps asset IDs to entries, a list of entries or a single entry.
+
+use std::collections::HashMap;
+
+use elements::AssetId;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use confidential::ConfidentialAssetLabel;
+use Chain;
+
+/// The metadata of an asset.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct AssetMetadata {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub ticker: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	/// The domain of the issuer.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub domain: Option<String>,
+	/// The number of decimal places of the asset's unit.
+	pub precision: u8,
+}
+
+impl AssetMetadata {
+	/// Format a value in satoshis in the asset's decimal unit.
+	pub fn format_value(&self, value: u64) -> String {
+		let precision = self.precision as usize;
+		if precision == 0 {
+			return value.to_string();
+		}
+		let digits = format!("{:0>width$}", value, width = precision + 1);
+		let (int, frac) = digits.split_at(digits.len() - precision);
+		format!("{}.{}", int, frac)
+	}
+}
+
+#[derive(Deserialize)]
+struct RegistryEntity {
+	domain: Option<String>,
+}
+
+/// An entry in the Blockstream asset registry.
+#[derive(Deserialize)]
+struct RegistryEntry {
+	asset_id: Option<AssetId>,
+	name: Option<String>,
+	ticker: Option<String>,
+	#[serde(default)]
+	precision: u8,
+	entity: Option<RegistryEntity>,
+}
+
+impl From<RegistryEntry> for AssetMetadata {
+	fn from(e: RegistryEntry) -> AssetMetadata {
+		AssetMetadata {
+			ticker: e.ticker,
+			name: e.name,
+			domain: e.entity.and_then(|e| e.domain),
+			precision: e.precision,
+		}
+	}
+}
+
+/// A registry of asset metadata.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AssetRegistry {
+	pub assets: HashMap<AssetId, AssetMetadata>,
+}
+
+impl AssetRegistry {
+	/// Parse a registry file in the Blockstream asset registry format.
+	pub fn from_json(s: &str) -> Result<AssetRegistry, String> {
+		let json: serde_json::Value = serde_json::from_str(s).map_err(|e| format!("invalid JSON: {}", e))?;
+
+		let entries = if json.get("asset_id").is_some() {
+			vec![(None, json)]
+		} else if let serde_json::Value::Array(list) = json {
+			list.into_iter().map(|e| (None, e)).collect()
+		} else if let serde_json::Value::Object(map) = json {
+			map.into_iter().map(|(id, e)| (Some(id), e)).collect()
+		} else {
+			return Err("registry must be an object or a list of entries".to_owned());
+		};
+
+		let mut assets = HashMap::new();
+		for (key, entry) in entries {
+			let entry: RegistryEntry = serde_json::from_value(entry)
+				.map_err(|e| format!("invalid registry entry: {}", e))?;
+			let id = match (key, entry.asset_id) {
+				(_, Some(id)) => id,
+				(Some(key), None) => key.parse().map_err(|_| format!("invalid asset ID: {}", key))?,
+				(None, None) => return Err("registry entry without asset ID".to_owned()),
+			};
+			if 10u64.checked_pow(entry.precision as u32).is_none() {
+				return Err(format!("invalid precision for asset {}: {}", id, entry.precision));
+			}
+			assets.insert(id, entry.into());
+		}
+		Ok(AssetRegistry { assets })
+	}
+}
+
+/// The metadata of the asset, from the registry of the chain or for well-known assets.
+pub fn asset_metadata(asset: AssetId, chain: Chain) -> Option<AssetMetadata> {
+	if let Some(metadata) = chain.registry.and_then(|r| r.assets.get(&asset)) {
+		return Some(metadata.clone());
+	}
+	let (ticker, name) = match ConfidentialAssetLabel::from_asset_id(asset, chain)? {
+		ConfidentialAssetLabel::LiquidBitcoin => (Some("L-BTC"), Some("Liquid Bitcoin")),
+		ConfidentialAssetLabel::LiquidTestnetBitcoin => (Some("tL-BTC"), Some("Liquid Testnet Bitcoin")),
+		ConfidentialAssetLabel::PolicyAsset => (None, None),
+	};
+	// Policy assets are pegged to bitcoin and share its precision.
+	Some(AssetMetadata {
+		ticker: ticker.map(str::to_owned),
+		name: name.map(str::to_owned),
+		domain: None,
+		precision: 8,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use Network;
+
+	const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+	const USDT_ENTRY: &str = r#"{
+		"asset_id": "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2",
+		"contract": {"precision": 8, "ticker": "USDt", "version": 0},
+		"entity": {"domain": "tether.to"},
+		"name": "Tether USD",
+		"precision": 8,
+		"ticker": "USDt"
+	}"#;
+
+	fn usdt() -> AssetMetadata {
+		AssetMetadata {
+			ticker: Some("USDt".to_owned()),
+			name: Some("Tether USD".to_owned()),
+			domain: Some("tether.to".to_owned()),
+			precision: 8,
+		}
+	}
+
+	#[test]
+	fn registry_formats() {
+		let id: AssetId = USDT.parse().unwrap();
+		let other = "1111111111111111111111111111111111111111111111111111111111111111";
+
+		// A single entry.
+		let registry = AssetRegistry::from_json(USDT_ENTRY).unwrap();
+		assert_eq!(registry.assets.len(), 1);
+		assert_eq!(registry.assets[&id], usdt());
+
+		// A list of entries.
+		let list = format!(r#"[{}, {{"asset_id": "{}", "precision": 2}}]"#, USDT_ENTRY, other);
+		let registry = AssetRegistry::from_json(&list).unwrap();
+		assert_eq!(registry.assets.len(), 2);
+		assert_eq!(registry.assets[&id], usdt());
+		assert_eq!(registry.assets[&other.parse().unwrap()].precision, 2);
+
+		// The index maps asset IDs to entries, which don't need to repeat the ID.
+		let index = format!(r#"{{"{}": {}, "{}": {{"ticker": "X"}}}}"#, USDT, USDT_ENTRY, other);
+		let registry = AssetRegistry::from_json(&index).unwrap();
+		assert_eq!(registry.assets.len(), 2);
+		assert_eq!(registry.assets[&id], usdt());
+		let x = &registry.assets[&other.parse().unwrap()];
+		assert_eq!((x.ticker.as_deref(), x.precision), (Some("X"), 0));
+	}
+
+	#[test]
+	fn registry_invalid() {
+		assert!(AssetRegistry::from_json("not json").is_err());
+		assert!(AssetRegistry::from_json("42").is_err());
+		assert!(AssetRegistry::from_json(r#"[{"ticker": "X"}]"#).is_err());
+		assert!(AssetRegistry::from_json(r#"{"not an asset id": {"ticker": "X"}}"#).is_err());
+		assert!(AssetRegistry::from_json(&format!(r#"{{"{}": {{"precision": "8"}}}}"#, USDT)).is_err());
+		assert!(AssetRegistry::from_json(&format!(r#"{{"{}": {{"precision": 20}}}}"#, USDT)).is_err());
+	}
+
+	#[test]
+	fn format_value() {
+		let precision = |precision| AssetMetadata { ticker: None, name: None, domain: None, precision };
+		assert_eq!(precision(0).format_value(12345), "12345");
+		assert_eq!(precision(8).format_value(123456789), "1.23456789");
+		assert_eq!(precision(8).format_value(100_000_000), "1.00000000");
+		assert_eq!(precision(8).format_value(5), "0.00000005");
+		assert_eq!(precision(8).format_value(0), "0.00000000");
+		assert_eq!(precision(2).format_value(99), "0.99");
+		assert_eq!(precision(19).format_value(u64::MAX), "1.8446744073709551615");
+	}
+
+	#[test]
+	fn registry_metadata() {
+		let registry = AssetRegistry::from_json(USDT_ENTRY).unwrap();
+		let chain = Chain { registry: Some(&registry), ..Chain::new(Network::Liquid) };
+		assert_eq!(asset_metadata(USDT.parse().unwrap(), chain), Some(usdt()));
+
+		let lbtc = asset_metadata(::LIQUID_POLICY_ASSET.parse().unwrap(), chain).unwrap();
+		assert_eq!((lbtc.ticker.unwrap(), lbtc.precision), ("L-BTC".to_owned(), 8));
+		assert_eq!(asset_metadata(USDT.parse().unwrap(), Chain::new(Network::Liquid)), None);
+
+		// Policy assets are only known on their own chain.
+		let testnet = Chain::new(Network::LiquidTestnet);
+		assert_eq!(asset_metadata(::LIQUID_POLICY_ASSET.parse().unwrap(), testnet), None);
+		let tlbtc = asset_metadata(::LIQUID_TESTNET_POLICY_ASSET.parse().unwrap(), testnet).unwrap();
+		assert_eq!(tlbtc.ticker.unwrap(), "tL-BTC");
+		let regtest = Chain::new(Network::ElementsRegtest);
+		assert_eq!(asset_metadata(::LIQUID_TESTNET_POLICY_ASSET.parse().unwrap(), regtest), None);
+		let policy = asset_metadata(::ELEMENTS_REGTEST_POLICY_ASSET.parse().unwrap(), regtest).unwrap();
+		assert_eq!((policy.ticker, policy.precision), (None, 8));
+	}
+}
@@ -1,15 +1,26 @@
 use elements::encode::serialize;
+use elements::bitcoin::hashes::{sha256, Hash};
 use elements::{
-	bitcoin, confidential, AssetIssuance, PeginData, PegoutData, Transaction, TxIn, TxInWitness,
-	TxOut, TxOutWitness, Txid, Wtxid, Script, Address,
+	bitcoin, confidential, AssetId, AssetIssuance, ContractHash, PeginData, PegoutData, Transaction, TxIn, TxInWitness,
+	TxOut, TxOutSecrets, TxOutWitness, Txid, Wtxid, Script, Address,
 };
-use elements::secp256k1_zkp::{RangeProof, SurjectionProof};
+use elements::secp256k1_zkp::{
+	self, Generator, PedersenCommitment, RangeProof, SurjectionProof, ZERO_TWEAK,
+};
+use elements::confidential::{AssetBlindingFactor, ValueBlindingFactor};
+use elements::slip77::MasterBlindingKey;
+use rand;
 
 use serde::{Deserialize, Serialize};
 
-use ::{GetInfo, Network, HexBytes};
+use ::{Chain, GetInfo, HexBytes};
+use pak::{pak_proof, PakProofInfo};
+use registry::asset_metadata;
 
-use confidential::{ConfidentialAssetInfo, ConfidentialNonceInfo, ConfidentialValueInfo};
+use confidential::{
+	ConfidentialAssetInfo, ConfidentialNonceInfo, ConfidentialValueInfo, RangeProofInfo,
+	SurjectionProofInfo, UnblindedOutputInfo,
+};
 
 const BTCNET: elements::bitcoin::Network = elements::bitcoin::Network::Bitcoin;
 
@@ -19,19 +30,172 @@ pub struct AssetIssuanceInfo {
 	pub asset_entropy: Option<HexBytes>,
 	pub amount: Option<ConfidentialValueInfo>,
 	pub inflation_keys: Option<ConfidentialValueInfo>,
+
+	// The fields below are calculated from the issuing input and are
+	// ignored when creating transactions.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_reissuance: Option<bool>,
+	/// The contract hash of a new issuance.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub contract_hash: Option<ContractHash>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub entropy: Option<sha256::Midstate>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub asset_id: Option<AssetId>,
+	/// The reissuance token of a new issuance.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub token_id: Option<AssetId>,
+	/// Whether the reissuance token is the one for confidential issuances.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub confidential_token: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub unblinded_amount: Option<UnblindedOutputInfo>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub unblinded_inflation_keys: Option<UnblindedOutputInfo>,
 }
 
 impl GetInfo<AssetIssuanceInfo> for AssetIssuance {
-	fn get_info(&self, network: Network) -> AssetIssuanceInfo {
+	fn get_info(&self, chain: Chain) -> AssetIssuanceInfo {
 		AssetIssuanceInfo {
 			asset_blinding_nonce: Some(self.asset_blinding_nonce[..].into()),
 			asset_entropy: Some(self.asset_entropy[..].into()),
-			amount: Some(self.amount.get_info(network)),
-			inflation_keys: Some(self.inflation_keys.get_info(network)),
+			amount: Some(self.amount.get_info(chain)),
+			inflation_keys: Some(self.inflation_keys.get_info(chain)),
+			is_reissuance: None,
+			contract_hash: None,
+			entropy: None,
+			asset_id: None,
+			token_id: None,
+			confidential_token: None,
+			unblinded_amount: None,
+			unblinded_inflation_keys: None,
+		}
+	}
+}
+
+/// Whether the issuance of the input reissues an existing asset.
+///
+/// Reissuances commit to the blinding factor of the spent reissuance token,
+/// new issuances have a zero blinding nonce.
+pub fn is_reissuance(txin: &TxIn) -> bool {
+	txin.asset_issuance.asset_blinding_nonce != ZERO_TWEAK
+}
+
+/// The asset entropy of the issuance of the input.
+///
+/// For new issuances, this is calculated from the outpoint and the contract hash,
+/// reissuances carry the entropy of the original issuance.
+pub fn issuance_entropy(txin: &TxIn) -> sha256::Midstate {
+	if is_reissuance(txin) {
+		sha256::Midstate::from_inner(txin.asset_issuance.asset_entropy)
+	} else {
+		let contract_hash = ContractHash::from_inner(txin.asset_issuance.asset_entropy);
+		AssetId::generate_asset_entropy(txin.previous_output, contract_hash)
+	}
+}
+
+/// Get information about the issuance of the input, including the issued asset.
+fn issuance_info(txin: &TxIn, chain: Chain) -> AssetIssuanceInfo {
+	let mut info = txin.asset_issuance.get_info(chain);
+	let entropy = issuance_entropy(txin);
+	let reissuance = is_reissuance(txin);
+	info.is_reissuance = Some(reissuance);
+	info.entropy = Some(entropy);
+	info.asset_id = Some(AssetId::from_entropy(entropy));
+	if !reissuance {
+		// The token of an existing asset can't be derived from a reissuance,
+		// it depends on whether the original issuance was confidential.
+		let confidential = txin.asset_issuance.amount.is_confidential();
+		info.contract_hash = Some(ContractHash::from_inner(txin.asset_issuance.asset_entropy));
+		info.token_id = Some(AssetId::reissuance_token_from_entropy(entropy, confidential));
+		info.confidential_token = Some(confidential);
+	}
+	info
+}
+
+/// The contract hash of a JSON asset contract.
+///
+/// The contract is serialized canonically with its keys sorted, like the
+/// asset registry does.
+pub fn contract_hash(contract: &str) -> Result<ContractHash, String> {
+	ContractHash::from_json_contract(contract).map_err(|e| format!("invalid contract JSON: {}", e))
+}
+
+/// The assets resulting from the issuance with the given entropy.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct IssuanceIdsInfo {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub contract_hash: Option<ContractHash>,
+	pub entropy: sha256::Midstate,
+	pub asset_id: AssetId,
+	pub token_id: AssetId,
+	/// Whether the reissuance token is the one for confidential issuances.
+	pub confidential_token: bool,
+}
+
+impl IssuanceIdsInfo {
+	/// The IDs of a new issuance from the outpoint spent by the issuing input.
+	pub fn from_outpoint(
+		outpoint: elements::OutPoint,
+		contract_hash: ContractHash,
+		confidential: bool,
+	) -> IssuanceIdsInfo {
+		let mut info = IssuanceIdsInfo::from_entropy(
+			AssetId::generate_asset_entropy(outpoint, contract_hash),
+			confidential,
+		);
+		info.contract_hash = Some(contract_hash);
+		info
+	}
+
+	/// The IDs of an issuance from its asset entropy.
+	pub fn from_entropy(entropy: sha256::Midstate, confidential: bool) -> IssuanceIdsInfo {
+		IssuanceIdsInfo {
+			contract_hash: None,
+			entropy,
+			asset_id: AssetId::from_entropy(entropy),
+			token_id: AssetId::reissuance_token_from_entropy(entropy, confidential),
+			confidential_token: confidential,
 		}
 	}
 }
 
+/// The result of checking the contract committed to by an issuance.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct IssuanceContractVerificationInfo {
+	pub input: usize,
+	pub valid: bool,
+	pub contract_hash: ContractHash,
+	pub committed_contract_hash: ContractHash,
+	pub asset_id: AssetId,
+	pub token_id: AssetId,
+}
+
+/// Check whether the new issuance in the given input commits to the contract hash.
+pub fn verify_issuance_contract(
+	tx: &Transaction,
+	input: usize,
+	contract_hash: ContractHash,
+) -> Result<IssuanceContractVerificationInfo, String> {
+	let txin = tx.input.get(input).ok_or_else(|| format!("input {} doesn't exist", input))?;
+	if !txin.has_issuance() {
+		return Err(format!("input {} has no issuance", input));
+	}
+	if is_reissuance(txin) {
+		return Err(format!("input {} is a reissuance, which doesn't commit to a contract", input));
+	}
+	let committed = ContractHash::from_inner(txin.asset_issuance.asset_entropy);
+	let (asset_id, token_id) = txin.issuance_ids();
+	Ok(IssuanceContractVerificationInfo {
+		input,
+		valid: committed == contract_hash,
+		contract_hash,
+		committed_contract_hash: committed,
+		asset_id,
+		token_id,
+	})
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct PeginDataInfo {
 	pub outpoint: String,
@@ -46,11 +210,11 @@ pub struct PeginDataInfo {
 }
 
 impl<'tx> GetInfo<PeginDataInfo> for PeginData<'tx> {
-	fn get_info(&self, network: Network) -> PeginDataInfo {
+	fn get_info(&self, chain: Chain) -> PeginDataInfo {
 		PeginDataInfo {
 			outpoint: self.outpoint.to_string(),
 			value: self.value,
-			asset: self.asset.get_info(network),
+			asset: self.asset.get_info(chain),
 			genesis_hash: self.genesis_hash,
 			claim_script: self.claim_script.into(),
 			mainchain_tx_hex: self.tx.into(),
@@ -67,18 +231,26 @@ impl<'tx> GetInfo<PeginDataInfo> for PeginData<'tx> {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct InputWitnessInfo {
 	pub amount_rangeproof: Option<HexBytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub amount_rangeproof_info: Option<RangeProofInfo>,
 	pub inflation_keys_rangeproof: Option<HexBytes>,
 	#[serde(skip_serializing_if = "Option::is_none")]
+	pub inflation_keys_rangeproof_info: Option<RangeProofInfo>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub script_witness: Option<Vec<HexBytes>>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub pegin_witness: Option<Vec<HexBytes>>,
 }
 
 impl GetInfo<InputWitnessInfo> for TxInWitness {
-	fn get_info(&self, _network: Network) -> InputWitnessInfo {
+	fn get_info(&self, _chain: Chain) -> InputWitnessInfo {
 		InputWitnessInfo {
 			amount_rangeproof: self.amount_rangeproof.as_ref().map(|r| RangeProof::serialize(r).into()),
+			amount_rangeproof_info: self.amount_rangeproof.as_ref()
+				.and_then(|r| RangeProofInfo::from_proof(&RangeProof::serialize(r))),
 			inflation_keys_rangeproof: self.inflation_keys_rangeproof.as_ref().map(|r| RangeProof::serialize(r).into()),
+			inflation_keys_rangeproof_info: self.inflation_keys_rangeproof.as_ref()
+				.and_then(|r| RangeProofInfo::from_proof(&RangeProof::serialize(r))),
 			script_witness: if self.script_witness.len() > 0 {
 				Some(self.script_witness.iter().map(|w| w.clone().into()).collect())
 			} else {
@@ -102,7 +274,7 @@ pub struct InputScriptInfo {
 pub struct InputScript<'a>(pub &'a Script);
 
 impl<'a> ::GetInfo<InputScriptInfo> for InputScript<'a> {
-	fn get_info(&self, _network: Network) -> InputScriptInfo {
+	fn get_info(&self, _chain: Chain) -> InputScriptInfo {
 		InputScriptInfo {
 			hex: Some(self.0.to_bytes().into()),
 			asm: Some(self.0.asm()),
@@ -120,6 +292,9 @@ pub struct InputInfo {
 
 	pub is_pegin: Option<bool>,
 	pub has_issuance: Option<bool>,
+	/// The secrets of the spent output, used to blind the transaction outputs.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub prevout_secrets: Option<UnblindedOutputInfo>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub asset_issuance: Option<AssetIssuanceInfo>,
 	#[serde(skip_serializing_if = "Option::is_none")]
@@ -130,28 +305,29 @@ pub struct InputInfo {
 }
 
 impl GetInfo<InputInfo> for TxIn {
-	fn get_info(&self, network: Network) -> InputInfo {
+	fn get_info(&self, chain: Chain) -> InputInfo {
 		InputInfo {
 			// fmt::Display on elements outpoints show the `[elements]` prefix
 			prevout: Some(format!("{}:{}", self.previous_output.txid, self.previous_output.vout)),
 			txid: Some(self.previous_output.txid),
 			vout: Some(self.previous_output.vout),
 			sequence: Some(self.sequence.to_consensus_u32()),
-			script_sig: Some(::GetInfo::get_info(&InputScript(&self.script_sig), network)),
+			script_sig: Some(::GetInfo::get_info(&InputScript(&self.script_sig), chain)),
 
 			is_pegin: Some(self.is_pegin),
 			has_issuance: Some(self.has_issuance()),
+			prevout_secrets: None,
 			asset_issuance: if self.has_issuance() {
-				Some(self.asset_issuance.get_info(network))
+				Some(issuance_info(self, chain))
 			} else {
 				None
 			},
 			witness: if !self.witness.is_empty() {
-				Some(self.witness.get_info(network))
+				Some(self.witness.get_info(chain))
 			} else {
 				None
 			},
-			pegin_data: self.pegin_data().map(|p| p.get_info(network)),
+			pegin_data: self.pegin_data().map(|p| p.get_info(chain)),
 		}
 	}
 }
@@ -163,16 +339,20 @@ pub struct PegoutDataInfo {
 	pub genesis_hash: bitcoin::BlockHash,
 	pub script_pub_key: hal::tx::OutputScriptInfo,
 	pub extra_data: Vec<HexBytes>,
+	/// The PAK proof in the extra data, if it holds one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pak_proof: Option<PakProofInfo>,
 }
 
 impl<'tx> GetInfo<PegoutDataInfo> for PegoutData<'tx> {
-	fn get_info(&self, network: Network) -> PegoutDataInfo {
+	fn get_info(&self, chain: Chain) -> PegoutDataInfo {
 		PegoutDataInfo {
 			value: self.value,
-			asset: self.asset.get_info(network),
+			asset: self.asset.get_info(chain),
 			genesis_hash: self.genesis_hash,
 			script_pub_key: hal::GetInfo::get_info(&hal::tx::OutputScript(&self.script_pubkey), BTCNET),
 			extra_data: self.extra_data.iter().map(|w| w.clone().into()).collect(),
+			pak_proof: pak_proof(&self.extra_data).ok(),
 		}
 	}
 }
@@ -180,14 +360,21 @@ impl<'tx> GetInfo<PegoutDataInfo> for PegoutData<'tx> {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct OutputWitnessInfo {
 	pub surjection_proof: Option<HexBytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub surjection_proof_info: Option<SurjectionProofInfo>,
 	pub rangeproof: Option<HexBytes>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rangeproof_info: Option<RangeProofInfo>,
 }
 
 impl GetInfo<OutputWitnessInfo> for TxOutWitness {
-	fn get_info(&self, _network: Network) -> OutputWitnessInfo {
+	fn get_info(&self, _chain: Chain) -> OutputWitnessInfo {
 		OutputWitnessInfo {
 			surjection_proof: self.surjection_proof.as_ref().map(|p| SurjectionProof::serialize(p).into()),
+			surjection_proof_info: self.surjection_proof.as_ref()
+				.and_then(|p| SurjectionProofInfo::from_proof(&SurjectionProof::serialize(p))),
 			rangeproof: self.rangeproof.as_ref().map(|p| RangeProof::serialize(p).into()),
+			rangeproof_info: self.rangeproof.as_ref().and_then(|p| RangeProofInfo::from_proof(&RangeProof::serialize(p))),
 		}
 	}
 }
@@ -205,7 +392,7 @@ pub struct OutputScriptInfo {
 pub struct OutputScript<'a>(pub &'a Script);
 
 impl<'a> ::GetInfo<OutputScriptInfo> for OutputScript<'a> {
-	fn get_info(&self, network: Network) -> OutputScriptInfo {
+	fn get_info(&self, chain: Chain) -> OutputScriptInfo {
 		OutputScriptInfo {
 			hex: Some(self.0.to_bytes().into()),
 			asm: Some(self.0.asm()),
@@ -222,12 +409,14 @@ impl<'a> ::GetInfo<OutputScriptInfo> for OutputScript<'a> {
 					"p2wpkh"
 				} else if self.0.is_v0_p2wsh() {
 					"p2wsh"
+				} else if self.0.is_v1_p2tr() {
+					"p2tr"
 				} else {
 					"unknown"
 				}
 				.to_owned(),
 			),
-			address: Address::from_script(&self.0, None, network.address_params()),
+			address: Address::from_script(&self.0, None, chain.network.address_params()),
 		}
 	}
 }
@@ -240,15 +429,20 @@ pub struct OutputInfo {
 	pub asset: Option<ConfidentialAssetInfo>,
 	pub value: Option<ConfidentialValueInfo>,
 	pub nonce: Option<ConfidentialNonceInfo>,
+	/// The pubkey to blind the output to when creating a transaction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub blinding_pubkey: Option<secp256k1_zkp::PublicKey>,
 	pub witness: Option<OutputWitnessInfo>,
 	pub is_fee: Option<bool>,
 
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub pegout_data: Option<PegoutDataInfo>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub unblinded: Option<UnblindedOutputInfo>,
 }
 
 impl GetInfo<OutputInfo> for TxOut {
-	fn get_info(&self, network: Network) -> OutputInfo {
+	fn get_info(&self, chain: Chain) -> OutputInfo {
 		let is_fee = {
 			// An output is fee if both the asset and the value are explicit
 			// and if the output script is empty.
@@ -264,14 +458,21 @@ impl GetInfo<OutputInfo> for TxOut {
 			exp_ass && exp_val && self.script_pubkey.len() == 0
 		};
 
+		let mut value = self.value.get_info(chain);
+		if let (confidential::Asset::Explicit(asset), confidential::Value::Explicit(v)) = (self.asset, self.value) {
+			value.decimal_value = asset_metadata(asset, chain).map(|m| m.format_value(v));
+		}
+
 		OutputInfo {
-			script_pub_key: Some(::GetInfo::get_info(&OutputScript(&self.script_pubkey), network)),
-			asset: Some(self.asset.get_info(network)),
-			value: Some(self.value.get_info(network)),
-			nonce: Some(self.nonce.get_info(network)),
-			witness: Some(self.witness.get_info(network)),
+			script_pub_key: Some(::GetInfo::get_info(&OutputScript(&self.script_pubkey), chain)),
+			asset: Some(self.asset.get_info(chain)),
+			value: Some(value),
+			nonce: Some(self.nonce.get_info(chain)),
+			blinding_pubkey: None,
+			witness: Some(self.witness.get_info(chain)),
 			is_fee: Some(is_fee),
-			pegout_data: self.pegout_data().map(|p| p.get_info(network)),
+			pegout_data: self.pegout_data().map(|p| p.get_info(chain)),
+			unblinded: None,
 		}
 	}
 }
@@ -291,7 +492,7 @@ pub struct TransactionInfo {
 }
 
 impl GetInfo<TransactionInfo> for Transaction {
-	fn get_info(&self, network: Network) -> TransactionInfo {
+	fn get_info(&self, chain: Chain) -> TransactionInfo {
 		TransactionInfo {
 			txid: Some(self.txid()),
 			wtxid: Some(self.wtxid()),
@@ -301,8 +502,640 @@ impl GetInfo<TransactionInfo> for Transaction {
 			size: Some(serialize(self).len()),
 			weight: Some(self.weight() as usize),
 			vsize: Some((self.weight() / 4) as usize),
-			inputs: Some(self.input.iter().map(|i| i.get_info(network)).collect()),
-			outputs: Some(self.output.iter().map(|o| o.get_info(network)).collect()),
+			inputs: Some(self.input.iter().map(|i| i.get_info(chain)).collect()),
+			outputs: Some(self.output.iter().map(|o| o.get_info(chain)).collect()),
 		}
 	}
 }
+
+/// The asset generators of the surjection proof domain of the transaction,
+/// given the assets of the outputs spent by its inputs.
+///
+/// Like in Elements, the domain consists of the spent assets, each followed by
+/// the assets and reissuance tokens issued in the input.
+pub fn surjection_domain(
+	tx: &Transaction,
+	spent_assets: &[confidential::Asset],
+) -> Result<Vec<Generator>, String> {
+	if spent_assets.len() != tx.input.len() {
+		return Err(format!(
+			"got {} spent assets for {} inputs", spent_assets.len(), tx.input.len(),
+		));
+	}
+
+	let secp = secp256k1_zkp::Secp256k1::signing_only();
+	let mut domain = Vec::new();
+	for (i, (input, asset)) in tx.input.iter().zip(spent_assets.iter()).enumerate() {
+		domain.push(match *asset {
+			confidential::Asset::Explicit(id) => Generator::new_unblinded(&secp, id.into_tag()),
+			confidential::Asset::Confidential(gen) => gen,
+			confidential::Asset::Null => return Err(format!("spent asset of input {} is null", i)),
+		});
+		if input.has_issuance() {
+			let (asset, token) = input.issuance_ids();
+			if !input.asset_issuance.amount.is_null() {
+				domain.push(Generator::new_unblinded(&secp, asset.into_tag()));
+			}
+			if !input.asset_issuance.inflation_keys.is_null() {
+				domain.push(Generator::new_unblinded(&secp, token.into_tag()));
+			}
+		}
+	}
+	Ok(domain)
+}
+
+impl TransactionInfo {
+	/// Verify the surjection proofs of the outputs against the given domain.
+	pub fn verify_surjection_proofs(&mut self, tx: &Transaction, domain: &[Generator]) {
+		let secp = secp256k1_zkp::Secp256k1::verification_only();
+		let outputs = match self.outputs {
+			Some(ref mut outputs) => outputs,
+			None => return,
+		};
+		for (output, txout) in outputs.iter_mut().zip(tx.output.iter()) {
+			let gen = match txout.asset {
+				confidential::Asset::Confidential(gen) => gen,
+				_ => continue,
+			};
+			let proof = match txout.witness.surjection_proof {
+				Some(ref proof) => proof,
+				None => continue,
+			};
+			let info = output.witness.as_mut().and_then(|w| w.surjection_proof_info.as_mut());
+			if let Some(info) = info {
+				info.verified = Some(proof.verify(&secp, gen, domain));
+			}
+		}
+	}
+}
+
+/// The result of verifying the proofs of an input.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct InputVerificationInfo {
+	pub index: usize,
+	/// Whether the issuance amount rangeproof is valid, if the amount is confidential.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub amount_rangeproof: Option<bool>,
+	/// Whether the issuance inflation keys rangeproof is valid, if the amount is confidential.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub inflation_keys_rangeproof: Option<bool>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<String>,
+}
+
+/// The result of verifying the proofs of an output.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct OutputVerificationInfo {
+	pub index: usize,
+	/// Whether the rangeproof is valid, if the value is confidential.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rangeproof: Option<bool>,
+	/// Whether the surjection proof is valid, if the asset is confidential.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub surjection_proof: Option<bool>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<String>,
+}
+
+/// The result of verifying the value balance and all proofs of a transaction.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct TransactionVerificationInfo {
+	/// Whether the balance and all proofs are valid.
+	pub valid: bool,
+	/// Whether the input and output value commitments balance.
+	pub balanced: bool,
+	pub inputs: Vec<InputVerificationInfo>,
+	pub outputs: Vec<OutputVerificationInfo>,
+}
+
+/// The value commitment of a value of the given asset, [None] for explicit zero values.
+fn value_commitment(
+	value: confidential::Value,
+	asset: confidential::Asset,
+) -> Result<Option<PedersenCommitment>, String> {
+	let secp = secp256k1_zkp::Secp256k1::signing_only();
+	match value {
+		confidential::Value::Null => Err("value is null".to_owned()),
+		confidential::Value::Explicit(0) => Ok(None),
+		confidential::Value::Explicit(v) => {
+			let gen = asset.into_asset_gen(&secp).ok_or("asset is null")?;
+			Ok(Some(PedersenCommitment::new_unblinded(&secp, v, gen)))
+		}
+		confidential::Value::Confidential(comm) => Ok(Some(comm)),
+	}
+}
+
+/// Verify a rangeproof, if the value is confidential.
+fn verify_rangeproof(
+	value: confidential::Value,
+	asset: confidential::Asset,
+	proof: Option<&RangeProof>,
+	extra_commit: &[u8],
+) -> Option<Result<(), String>> {
+	let secp = secp256k1_zkp::Secp256k1::new();
+	let comm = value.commitment()?;
+	Some(asset.into_asset_gen(&secp).ok_or_else(|| "asset is null".to_owned()).and_then(|gen| {
+		proof.ok_or_else(|| "rangeproof missing".to_owned())?
+			.verify(&secp, comm, extra_commit, gen)
+			.map(|_| ())
+			.map_err(|e| format!("invalid rangeproof: {}", e))
+	}))
+}
+
+/// Verify that the value commitments of the transaction balance and that all
+/// rangeproofs and surjection proofs are valid, given the outputs spent by its inputs.
+pub fn verify(tx: &Transaction, spent_outputs: &[TxOut]) -> Result<TransactionVerificationInfo, String> {
+	if spent_outputs.len() != tx.input.len() {
+		return Err(format!(
+			"got {} spent outputs for {} inputs", spent_outputs.len(), tx.input.len(),
+		));
+	}
+
+	let mut in_commits = Vec::new();
+	let mut inputs = Vec::with_capacity(tx.input.len());
+	for (index, (input, spent)) in tx.input.iter().zip(spent_outputs.iter()).enumerate() {
+		let mut info = InputVerificationInfo {
+			index,
+			amount_rangeproof: None,
+			inflation_keys_rangeproof: None,
+			errors: Vec::new(),
+		};
+		match value_commitment(spent.value, spent.asset) {
+			Ok(comm) => in_commits.extend(comm),
+			Err(e) => info.errors.push(format!("invalid spent output: {}", e)),
+		}
+
+		if input.has_issuance() {
+			let (asset, token) = input.issuance_ids();
+			let issuance = &input.asset_issuance;
+			let issued = [
+				(issuance.amount, asset, &input.witness.amount_rangeproof, "amount"),
+				(issuance.inflation_keys, token, &input.witness.inflation_keys_rangeproof, "inflation keys"),
+			];
+			let mut valid = [None, None];
+			for (i, &(value, asset, proof, name)) in issued.iter().enumerate() {
+				if value.is_null() {
+					continue;
+				}
+				let asset = confidential::Asset::Explicit(asset);
+				match value_commitment(value, asset) {
+					Ok(comm) => in_commits.extend(comm),
+					Err(e) => info.errors.push(format!("invalid issuance {}: {}", name, e)),
+				}
+				// Issuance rangeproofs commit to an empty script.
+				let result = verify_rangeproof(value, asset, proof.as_deref(), &[]);
+				if let Some(Err(ref e)) = result {
+					info.errors.push(format!("issuance {}: {}", name, e));
+				}
+				valid[i] = result.map(|r| r.is_ok());
+			}
+			info.amount_rangeproof = valid[0];
+			info.inflation_keys_rangeproof = valid[1];
+		}
+		inputs.push(info);
+	}
+
+	let spent_assets = spent_outputs.iter().map(|o| o.asset).collect::<Vec<_>>();
+	let domain = surjection_domain(tx, &spent_assets);
+
+	let secp = secp256k1_zkp::Secp256k1::new();
+	let mut out_commits = Vec::new();
+	let mut outputs = Vec::with_capacity(tx.output.len());
+	for (index, output) in tx.output.iter().enumerate() {
+		let mut info = OutputVerificationInfo {
+			index,
+			rangeproof: None,
+			surjection_proof: None,
+			errors: Vec::new(),
+		};
+		match value_commitment(output.value, output.asset) {
+			Ok(comm) => out_commits.extend(comm),
+			Err(e) => info.errors.push(e),
+		}
+
+		let proof = output.witness.rangeproof.as_deref();
+		let result = verify_rangeproof(output.value, output.asset, proof, output.script_pubkey.as_bytes());
+		if let Some(Err(ref e)) = result {
+			info.errors.push(e.clone());
+		}
+		info.rangeproof = result.map(|r| r.is_ok());
+
+		if let Some(gen) = output.asset.commitment() {
+			let valid = match (output.witness.surjection_proof.as_ref(), domain.as_ref()) {
+				(None, _) => {
+					info.errors.push("surjection proof missing".to_owned());
+					false
+				}
+				(Some(_), Err(e)) => {
+					info.errors.push(format!("can't verify surjection proof: {}", e));
+					false
+				}
+				(Some(proof), Ok(domain)) => {
+					let valid = proof.verify(&secp, gen, domain);
+					if !valid {
+						info.errors.push("invalid surjection proof".to_owned());
+					}
+					valid
+				}
+			};
+			info.surjection_proof = Some(valid);
+		}
+		outputs.push(info);
+	}
+
+	let balanced = secp256k1_zkp::verify_commitments_sum_to_equal(&secp, &in_commits, &out_commits);
+	Ok(TransactionVerificationInfo {
+		valid: balanced
+			&& inputs.iter().all(|i| i.errors.is_empty())
+			&& outputs.iter().all(|o| o.errors.is_empty()),
+		balanced,
+		inputs,
+		outputs,
+	})
+}
+
+/// Blind all outputs that have their blinding pubkey set as nonce, like Elements does.
+///
+/// The secrets of the spent outputs are required for all inputs.
+/// Issuances are only supported with explicit amounts.
+pub fn blind(tx: &mut Transaction, spent_secrets: &[TxOutSecrets]) -> Result<(), String> {
+	if spent_secrets.len() != tx.input.len() {
+		return Err(format!(
+			"got {} spent output secrets for {} inputs", spent_secrets.len(), tx.input.len(),
+		));
+	}
+
+	// Issuances are part of the surjection proof domain and of the value balance.
+	let mut secrets = Vec::new();
+	for (input, spent) in tx.input.iter().zip(spent_secrets.iter()) {
+		secrets.push(*spent);
+		if input.has_issuance() {
+			let (asset, token) = input.issuance_ids();
+			let issuance = &input.asset_issuance;
+			for &(value, id) in [(issuance.amount, asset), (issuance.inflation_keys, token)].iter() {
+				match value {
+					confidential::Value::Null => {}
+					confidential::Value::Explicit(v) => secrets.push(TxOutSecrets::new(
+						id, AssetBlindingFactor::zero(), v, ValueBlindingFactor::zero(),
+					)),
+					confidential::Value::Confidential(_) => {
+						return Err("confidential issuance amounts are not supported".to_owned());
+					}
+				}
+			}
+		}
+	}
+
+	// Fee outputs are never blinded, so at least one other output must carry a
+	// blinding pubkey for the value balance to be absorbed somewhere.
+	if !tx.output.iter().any(|o| !o.is_fee() && o.nonce.is_confidential()) {
+		return Err("no non-fee output has a blinding pubkey".to_owned());
+	}
+
+	let secp = secp256k1_zkp::Secp256k1::new();
+	tx.blind(&mut rand::thread_rng(), &secp, &secrets, false).map_err(|e| e.to_string())?;
+	Ok(())
+}
+
+/// Keys to unblind confidential outputs with.
+#[derive(Clone, Debug, Default)]
+pub struct UnblindingKeys {
+	/// Blinding private keys that are tried on every output.
+	pub blinding_keys: Vec<secp256k1_zkp::SecretKey>,
+	/// A SLIP-77 master blinding key to derive the output blinding keys from.
+	pub slip77: Option<MasterBlindingKey>,
+	/// Issuance blinding keys that are tried on every issuance.
+	pub issuance_blinding_keys: Vec<secp256k1_zkp::SecretKey>,
+}
+
+impl UnblindingKeys {
+	/// Try to unblind the output with each of the keys.
+	pub fn unblind(&self, txout: &TxOut) -> Option<TxOutSecrets> {
+		let secp = secp256k1_zkp::Secp256k1::verification_only();
+		let derived = self.slip77.as_ref().map(|k| k.derive_blinding_key(&txout.script_pubkey));
+		self.blinding_keys.iter().chain(derived.iter()).filter_map(|k| txout.unblind(&secp, *k).ok()).next()
+	}
+
+	/// Try to unblind the issuance amount and inflation keys of the input with each
+	/// of the issuance blinding keys.
+	pub fn unblind_issuance(&self, txin: &TxIn) -> (Option<TxOutSecrets>, Option<TxOutSecrets>) {
+		let mut amount = None;
+		let mut inflation_keys = None;
+		for key in &self.issuance_blinding_keys {
+			let (a, i) = unblind_issuance(txin, *key);
+			amount = amount.or(a);
+			inflation_keys = inflation_keys.or(i);
+		}
+		(amount, inflation_keys)
+	}
+}
+
+/// Unblind the confidential issuance amount and inflation keys of the input.
+///
+/// Unlike outputs, issuance rangeproofs use the issuance blinding key as nonce
+/// directly and don't commit to a script. Issued assets are never blinded.
+pub fn unblind_issuance(
+	txin: &TxIn,
+	key: secp256k1_zkp::SecretKey,
+) -> (Option<TxOutSecrets>, Option<TxOutSecrets>) {
+	let secp = secp256k1_zkp::Secp256k1::new();
+	let entropy = issuance_entropy(txin);
+	let confidential = txin.asset_issuance.amount.is_confidential();
+	let unblind = |value: confidential::Value, proof: Option<&RangeProof>, assets: &[AssetId]| {
+		let (commitment, proof) = (value.commitment()?, proof?);
+		assets.iter().filter_map(|asset| {
+			let generator = Generator::new_unblinded(&secp, asset.into_tag());
+			let (opening, _) = proof.rewind(&secp, commitment, key, &[], generator).ok()?;
+			Some(TxOutSecrets::new(
+				*asset,
+				AssetBlindingFactor::zero(),
+				opening.value,
+				ValueBlindingFactor::from_slice(opening.blinding_factor.as_ref()).ok()?,
+			))
+		}).next()
+	};
+	let amount = unblind(
+		txin.asset_issuance.amount,
+		txin.witness.amount_rangeproof.as_deref(),
+		&[AssetId::from_entropy(entropy)],
+	);
+	// Some blinders prove the inflation keys against the token ID they
+	// calculated before blinding the amount, so try that one as well.
+	let inflation_keys = unblind(
+		txin.asset_issuance.inflation_keys,
+		txin.witness.inflation_keys_rangeproof.as_deref(),
+		&[
+			AssetId::reissuance_token_from_entropy(entropy, confidential),
+			AssetId::reissuance_token_from_entropy(entropy, !confidential),
+		],
+	);
+	(amount, inflation_keys)
+}
+
+/// Get information about the transaction, revealing all outputs and issuances that can be
+/// unblinded.
+pub fn unblinded_info(tx: &Transaction, keys: &UnblindingKeys, chain: Chain) -> TransactionInfo {
+	let mut info = tx.get_info(chain);
+	if let Some(ref mut inputs) = info.inputs {
+		for (input, txin) in inputs.iter_mut().zip(tx.input.iter()) {
+			if let Some(ref mut issuance) = input.asset_issuance {
+				let (amount, inflation_keys) = keys.unblind_issuance(txin);
+				issuance.unblinded_amount = amount.map(|s| s.get_info(chain));
+				issuance.unblinded_inflation_keys = inflation_keys.map(|s| s.get_info(chain));
+			}
+		}
+	}
+	if let Some(ref mut outputs) = info.outputs {
+		for (output, txout) in outputs.iter_mut().zip(tx.output.iter()) {
+			output.unblinded = keys.unblind(txout).map(|s| s.get_info(chain));
+		}
+	}
+	info
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use elements::secp256k1_zkp::{PublicKey, Secp256k1, SecretKey, Tweak};
+	use bitcoin::hashes::hex::ToHex;
+
+	use Network;
+
+	fn asset() -> AssetId {
+		AssetId::from_slice(&[7; 32]).unwrap()
+	}
+
+	fn blinding_key() -> SecretKey {
+		SecretKey::from_slice(&[5; 32]).unwrap()
+	}
+
+	fn p2wpkh() -> Script {
+		let mut script = vec![0x00, 0x14];
+		script.extend_from_slice(&[1; 20]);
+		Script::from(script)
+	}
+
+	fn explicit_output(script: Script, value: u64, nonce: confidential::Nonce) -> TxOut {
+		TxOut {
+			asset: confidential::Asset::Explicit(asset()),
+			value: confidential::Value::Explicit(value),
+			nonce,
+			script_pubkey: script,
+			witness: TxOutWitness::default(),
+		}
+	}
+
+	/// A transaction spending a single explicit output of 100_000 with a blinded
+	/// payment of 99_000 and a fee of 1_000.
+	fn unblinded_tx() -> (Transaction, Vec<TxOutSecrets>) {
+		let secp = Secp256k1::new();
+		let blinding_pk = PublicKey::from_secret_key(&secp, &blinding_key());
+		let tx = Transaction {
+			version: 2,
+			lock_time: elements::PackedLockTime::ZERO,
+			input: vec![TxIn::default()],
+			output: vec![
+				explicit_output(p2wpkh(), 99_000, confidential::Nonce::from(blinding_pk)),
+				explicit_output(Script::new(), 1_000, confidential::Nonce::Null),
+			],
+		};
+		let secrets = vec![TxOutSecrets::new(
+			asset(), AssetBlindingFactor::zero(), 100_000, ValueBlindingFactor::zero(),
+		)];
+		(tx, secrets)
+	}
+
+	#[test]
+	fn blind_unblind_roundtrip() {
+		let (mut tx, secrets) = unblinded_tx();
+		blind(&mut tx, &secrets).unwrap();
+		assert!(tx.output[0].value.is_confidential());
+		assert!(tx.output[0].asset.is_confidential());
+		assert!(tx.output[1].is_fee());
+
+		let keys = UnblindingKeys {
+			blinding_keys: vec![SecretKey::from_slice(&[6; 32]).unwrap(), blinding_key()],
+			..Default::default()
+		};
+		let unblinded = keys.unblind(&tx.output[0]).unwrap();
+		assert_eq!(unblinded.asset, asset());
+		assert_eq!(unblinded.value, 99_000);
+		assert!(keys.unblind(&tx.output[1]).is_none());
+	}
+
+	#[test]
+	fn blind_invalid() {
+		// Only the fee output has a blinding pubkey.
+		let (mut tx, secrets) = unblinded_tx();
+		let nonce = tx.output[0].nonce;
+		tx.output[0].nonce = confidential::Nonce::Null;
+		tx.output[1].nonce = nonce;
+		assert!(blind(&mut tx, &secrets).is_err());
+
+		// No output has a blinding pubkey.
+		tx.output[1].nonce = confidential::Nonce::Null;
+		assert!(blind(&mut tx, &secrets).is_err());
+
+		// Secrets missing for the input.
+		let (mut tx, _) = unblinded_tx();
+		assert!(blind(&mut tx, &[]).is_err());
+	}
+
+	/// The output spent by the unblinded transaction.
+	fn spent_output() -> TxOut {
+		explicit_output(p2wpkh(), 100_000, confidential::Nonce::Null)
+	}
+
+	#[test]
+	fn verify_blinded_tx() {
+		let (mut tx, secrets) = unblinded_tx();
+		blind(&mut tx, &secrets).unwrap();
+
+		let info = verify(&tx, &[spent_output()]).unwrap();
+		assert!(info.valid, "{:?}", info);
+		assert!(info.balanced);
+		assert_eq!(info.outputs[0].rangeproof, Some(true));
+		assert_eq!(info.outputs[0].surjection_proof, Some(true));
+		assert_eq!(info.outputs[1].rangeproof, None);
+		assert_eq!(info.outputs[1].surjection_proof, None);
+
+		assert!(verify(&tx, &[]).is_err());
+	}
+
+	#[test]
+	fn verify_tampered_value() {
+		let (mut tx, secrets) = unblinded_tx();
+		blind(&mut tx, &secrets).unwrap();
+
+		// Commit to a different amount of the same asset.
+		let secp = Secp256k1::new();
+		let gen = tx.output[0].asset.commitment().unwrap();
+		let blinding = Tweak::from_slice(&[9; 32]).unwrap();
+		tx.output[0].value = confidential::Value::Confidential(
+			PedersenCommitment::new(&secp, 98_000, blinding, gen),
+		);
+
+		let info = verify(&tx, &[spent_output()]).unwrap();
+		assert!(!info.valid);
+		assert!(!info.balanced);
+		assert_eq!(info.outputs[0].rangeproof, Some(false));
+		assert_eq!(info.outputs[0].surjection_proof, Some(true));
+		assert!(!info.outputs[0].errors.is_empty());
+	}
+
+	#[test]
+	fn verify_wrong_input_asset() {
+		let (mut tx, secrets) = unblinded_tx();
+		blind(&mut tx, &secrets).unwrap();
+
+		let mut spent = spent_output();
+		spent.asset = confidential::Asset::Explicit(AssetId::from_slice(&[8; 32]).unwrap());
+		let info = verify(&tx, &[spent]).unwrap();
+		assert!(!info.valid);
+		assert!(!info.balanced);
+		assert_eq!(info.outputs[0].rangeproof, Some(true));
+		assert_eq!(info.outputs[0].surjection_proof, Some(false));
+		assert!(info.inputs[0].errors.is_empty());
+
+		// A null spent asset can't be part of the surjection domain.
+		let mut spent = spent_output();
+		spent.asset = confidential::Asset::Null;
+		let info = verify(&tx, &[spent]).unwrap();
+		assert!(!info.valid);
+		assert!(!info.inputs[0].errors.is_empty());
+		assert_eq!(info.outputs[0].surjection_proof, Some(false));
+	}
+
+	#[test]
+	fn verify_surjection_proof_info() {
+		let (mut tx, secrets) = unblinded_tx();
+		blind(&mut tx, &secrets).unwrap();
+		let surjection_verified = |assets: &[confidential::Asset]| {
+			let mut info = tx.get_info(Chain::new(Network::ElementsRegtest));
+			let domain = surjection_domain(&tx, assets).unwrap();
+			info.verify_surjection_proofs(&tx, &domain);
+			let outputs = info.outputs.unwrap();
+			assert!(outputs[1].witness.as_ref().unwrap().surjection_proof_info.is_none());
+			outputs[0].witness.as_ref().unwrap().surjection_proof_info.as_ref().unwrap().verified
+		};
+
+		assert_eq!(surjection_verified(&[confidential::Asset::Explicit(asset())]), Some(true));
+		let other = AssetId::from_slice(&[8; 32]).unwrap();
+		assert_eq!(surjection_verified(&[confidential::Asset::Explicit(other)]), Some(false));
+
+		assert!(surjection_domain(&tx, &[]).is_err());
+		assert!(surjection_domain(&tx, &[confidential::Asset::Null]).is_err());
+	}
+
+
+	#[test]
+	fn unblind_confidential_issuance() {
+		let secp = Secp256k1::new();
+		let mut txin = TxIn {
+			asset_issuance: AssetIssuance {
+				asset_blinding_nonce: ZERO_TWEAK,
+				asset_entropy: [3; 32],
+				amount: confidential::Value::Explicit(1_000),
+				inflation_keys: confidential::Value::Explicit(1),
+			},
+			..Default::default()
+		};
+		// The token ID that rust-elements proves the inflation keys against.
+		let (asset_id, token_id) = txin.issuance_ids();
+		let amount_key = SecretKey::from_slice(&[10; 32]).unwrap();
+		let token_key = SecretKey::from_slice(&[11; 32]).unwrap();
+		let amount_vbf = ValueBlindingFactor::from_slice(&[12; 32]).unwrap();
+		let token_vbf = ValueBlindingFactor::from_slice(&[13; 32]).unwrap();
+		txin.blind_issuances_with_bfs(&secp, amount_vbf, token_vbf, amount_key, token_key).unwrap();
+		assert!(txin.asset_issuance.amount.is_confidential());
+		assert!(txin.asset_issuance.inflation_keys.is_confidential());
+
+		let (amount, inflation_keys) = unblind_issuance(&txin, amount_key);
+		let amount = amount.unwrap();
+		assert_eq!(amount.asset, asset_id);
+		assert_eq!(amount.value, 1_000);
+		assert_eq!(amount.value_bf, amount_vbf);
+		assert!(inflation_keys.is_none());
+
+		let keys = UnblindingKeys {
+			issuance_blinding_keys: vec![blinding_key(), amount_key, token_key],
+			..Default::default()
+		};
+		let (amount, inflation_keys) = keys.unblind_issuance(&txin);
+		assert_eq!(amount.unwrap().value, 1_000);
+		let inflation_keys = inflation_keys.unwrap();
+		assert_eq!(inflation_keys.asset, token_id);
+		assert_eq!(inflation_keys.value, 1);
+		assert_eq!(inflation_keys.value_bf, token_vbf);
+
+		assert_eq!(unblind_issuance(&txin, blinding_key()), (None, None));
+	}
+
+
+	#[test]
+	fn registry_asset_issuance() {
+		// The Tether USD contract and the outpoint spent by its issuance on Liquid.
+		let contract = r#"{"ticker":"USDt","name":"Tether USD","precision":8,"version":0,"entity":{"domain":"tether.to"},"issuer_pubkey":"0337cceec0beea0232ebe14cba0197a9fbd45fcf2ec946749de920e71434c2b904"}"#;
+		let outpoint = "9596d259270ef5bac0020435e6d859aea633409483ba64e232b8ba04ce288668:0";
+
+		let hash = contract_hash(contract).unwrap();
+		assert_eq!(
+			hash.to_string(), "3c7f0a53c2ff5b99590620d7f6604a7a3a7bfbaaa6aa61f7bfc7833ca03cde82",
+		);
+		// The issuance commits to the unreversed hash.
+		assert_eq!(
+			hash.into_inner().to_hex(),
+			"82de3ca03c83c7bff761aaa6aafb7b3a7a4a60f6d7200659995bffc2530a7f3c",
+		);
+
+		let info = IssuanceIdsInfo::from_outpoint(outpoint.parse().unwrap(), hash, false);
+		assert_eq!(info.contract_hash, Some(hash));
+		assert_eq!(
+			info.asset_id.to_string(), "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2",
+		);
+
+		assert!(contract_hash(r#"{"ticker":"#).is_err());
+	}
+
+}